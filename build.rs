@@ -0,0 +1,47 @@
+fn main() {
+    export_grammar_versions();
+    #[cfg(feature = "grpc")]
+    compile_protos();
+    #[cfg(feature = "c-header")]
+    generate_c_header();
+}
+
+/// Exposes the locked versions of the tree-sitter grammars as `GRAMMAR_VERSION_<LANGUAGE>`
+/// environment variables, reported by the service's `/version` endpoint.
+fn export_grammar_versions() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let lock_path = format!("{}/Cargo.lock", crate_dir);
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-changed=build.rs");
+    let lock = std::fs::read_to_string(lock_path).unwrap_or_default();
+    for (package, language) in [("tree-sitter-java", "JAVA"), ("tree-sitter-python", "PYTHON"), ("tree-sitter-r", "R")] {
+        let version = lock
+            .split("[[package]]")
+            .find(|entry| entry.contains(&format!("name = \"{}\"", package)))
+            .and_then(|entry| entry.lines().find_map(|line| line.trim().strip_prefix("version = ")))
+            .map_or("unknown", |version| version.trim_matches('"'));
+        println!("cargo:rustc-env=GRAMMAR_VERSION_{}={}", language, version);
+    }
+}
+
+/// Generates the gRPC server and client from `proto/lint.proto`, using a vendored `protoc`
+/// unless `PROTOC` points at one already.
+#[cfg(feature = "grpc")]
+fn compile_protos() {
+    println!("cargo:rerun-if-changed=proto/lint.proto");
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path().expect("No vendored protoc for this platform"));
+    }
+    tonic_build::compile_protos("proto/lint.proto").expect("Unable to compile proto/lint.proto");
+}
+
+/// Regenerates `include/lintymclintface.h` from the `extern "C"` functions in `src/ffi.rs`.
+#[cfg(feature = "c-header")]
+fn generate_c_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    cbindgen::generate(&crate_dir)
+        .expect("Unable to generate C header")
+        .write_to_file(format!("{}/include/lintymclintface.h", crate_dir));
+}
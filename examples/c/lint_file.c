@@ -0,0 +1,47 @@
+/*
+ * Lints a file through the C ABI.
+ *
+ *   cargo build --release
+ *   cc -Iinclude examples/c/lint_file.c -Ltarget/release -llintymclintface -o lint_file
+ *   LD_LIBRARY_PATH=target/release ./lint_file java Test.java
+ */
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "lintymclintface.h"
+
+int main(int argc, char **argv) {
+    if (argc != 3) {
+        fprintf(stderr, "Usage: %s <language> <file>\n", argv[0]);
+        return 2;
+    }
+
+    FILE *f = fopen(argv[2], "rb");
+    if (f == NULL) {
+        perror(argv[2]);
+        return 2;
+    }
+    fseek(f, 0, SEEK_END);
+    long size = ftell(f);
+    fseek(f, 0, SEEK_SET);
+    char *code = malloc(size > 0 ? size : 1);
+    size_t len = fread(code, 1, size, f);
+    fclose(f);
+
+    LmcfDiagnosticList *list = lmcf_lint(argv[1], code, len);
+    free(code);
+
+    if (list->error != NULL) {
+        fprintf(stderr, "%s: error: %s\n", argv[2], list->error);
+        lmcf_diagnostics_free(list);
+        return 2;
+    }
+    for (size_t i = 0; i < list->len; i++) {
+        const LmcfDiagnostic *d = &list->diagnostics[i];
+        printf("%s:%zu:%zu: %s: %s [%s]\n", argv[2], d->line, d->column,
+               d->severity == LMCF_ERROR ? "error" : "warning", d->message, d->rule);
+    }
+    int status = list->len == 0 ? 0 : 1;
+    lmcf_diagnostics_free(list);
+    return status;
+}
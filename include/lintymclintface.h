@@ -0,0 +1,86 @@
+#ifndef LINTYMCLINTFACE_H
+#define LINTYMCLINTFACE_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit; rebuild with `cargo build --features c-header`. */
+
+#include <stddef.h>
+
+/*
+ Severity of a diagnostic.
+ */
+typedef enum LmcfSeverity {
+  LMCF_WARNING = 0,
+  LMCF_ERROR = 1,
+} LmcfSeverity;
+
+/*
+ A single diagnostic. `message` and `rule` are NUL-terminated UTF-8 strings.
+ */
+typedef struct LmcfDiagnostic {
+  size_t line;
+  size_t column;
+  enum LmcfSeverity severity;
+  char *message;
+  char *rule;
+} LmcfDiagnostic;
+
+/*
+ The result of [`lmcf_lint`].
+
+ On success `error` is NULL and `diagnostics` points to `len` entries (NULL when `len` is 0).
+ On failure `error` holds a NUL-terminated description and `len` is 0.
+ */
+typedef struct LmcfDiagnosticList {
+  struct LmcfDiagnostic *diagnostics;
+  size_t len;
+  char *error;
+} LmcfDiagnosticList;
+
+/*
+ Lints `len` bytes of UTF-8 `code` written in `language` ("java", "python" or "r").
+
+ Returns a list that must be released with [`lmcf_diagnostics_free`]. Never returns NULL.
+
+ # Safety
+
+ `language` must be a NUL-terminated string and `code` must point to at least `len`
+ readable bytes (it may be NULL when `len` is 0).
+ */
+struct LmcfDiagnosticList *lmcf_lint(const char *language, const char *code, size_t len);
+
+/*
+ Releases a list returned by [`lmcf_lint`]. Passing NULL is a no-op.
+
+ # Safety
+
+ `list` must have been returned by [`lmcf_lint`] and not freed already.
+ */
+void lmcf_diagnostics_free(struct LmcfDiagnosticList *list);
+
+/*
+ Lints like [`lmcf_lint`] but returns the result as a JSON string.
+
+ On success the string is the same JSON array the CLI prints; on failure it is an
+ object of the form `{"error": "..."}`. Release it with [`lmcf_string_free`].
+
+ # Safety
+
+ Same requirements as [`lmcf_lint`].
+ */
+char *lmcf_lint_json(const char *language, const char *code, size_t len);
+
+/*
+ Releases a string returned by this library. Passing NULL is a no-op.
+
+ # Safety
+
+ `s` must have been returned by this library and not freed already.
+ */
+void lmcf_string_free(char *s);
+
+/*
+ Returns the library version as a static NUL-terminated string. Do not free it.
+ */
+const char *lmcf_version(void);
+
+#endif  /* LINTYMCLINTFACE_H */
@@ -0,0 +1,77 @@
+//! Caching of lint results for identical inputs.
+//!
+//! Results are keyed by a [`CacheKey`] covering everything that determines them: the
+//! language, the options that change the output, the version of this tool and the code.
+//! The service keeps recent results in memory; the CLI can keep them in a directory with
+//! [`DiskCache`] so that repeated runs over unchanged files skip parsing.
+
+use crate::{LintOptions, LintReport};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A SHA-256 digest, in hex, identifying the result of linting some code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey(String);
+
+impl CacheKey {
+    pub fn new(language: &str, code: &str, options: &LintOptions) -> Self {
+        let mut hasher = Sha256::new();
+        for part in [env!("CARGO_PKG_VERSION"), language, &fingerprint(options)] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        hasher.update(code.as_bytes());
+        CacheKey(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Describes the options that affect a successful result. The timeout is left out, as
+/// only successful results are cached and a parse that finishes gives the same result
+/// whatever the timeout was.
+fn fingerprint(options: &LintOptions) -> String {
+    format!(
+        "max_diagnostics={:?} rules={:?} disable={:?} min_severity={:?} target_version={:?}",
+        options.max_diagnostics, options.rules, options.disable, options.min_severity, options.target_version
+    )
+}
+
+/// Lint results stored as one JSON file per key under a directory.
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// Uses `dir` for the cache, creating it if needed.
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(DiskCache { dir: dir.to_path_buf() })
+    }
+
+    fn path(&self, key: &CacheKey) -> PathBuf {
+        let (prefix, rest) = key.as_str().split_at(2);
+        self.dir.join(prefix).join(format!("{}.json", rest))
+    }
+
+    /// Returns the cached result for `key`. Unreadable entries count as misses.
+    pub fn get(&self, key: &CacheKey) -> Option<LintReport> {
+        let data = fs::read(self.path(key)).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// Stores `report` under `key`, replacing the entry atomically so that concurrent runs
+    /// never see a partly written file.
+    pub fn put(&self, key: &CacheKey, report: &LintReport) -> io::Result<()> {
+        let path = self.path(key);
+        let dir = path.parent().expect("cache entries live in a subdirectory");
+        fs::create_dir_all(dir)?;
+        let tmp = dir.join(format!(".{}.{}.tmp", key.as_str(), std::process::id()));
+        fs::write(&tmp, serde_json::to_vec(report)?)?;
+        fs::rename(&tmp, &path)
+    }
+}
@@ -0,0 +1,448 @@
+//! `lintymclintface corpus run`: lints a checkout of a known-good repository and reports
+//! every file that does not meet the expectation, so that grammar regressions show up.
+//!
+//! Files listed in a known-failures manifest may fail without failing the run; the run
+//! points out the ones that have started to pass, so the manifest can shrink. With
+//! `--previous`, the results are compared with an earlier run's JSON summary, listing the
+//! files that regressed, those that were fixed and those whose diagnostics changed.
+
+use lintymclintface::{LintOptions, LinterError, Severity, SyntaxError};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::{info, warn};
+
+use crate::textfile::RunMetrics;
+
+/// What the files of a corpus must satisfy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Expectation {
+    /// No diagnostics at all.
+    Clean,
+    /// No diagnostics of error severity; warnings are allowed.
+    NoErrors,
+}
+
+impl FromStr for Expectation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clean" => Ok(Expectation::Clean),
+            "no-errors" => Ok(Expectation::NoErrors),
+            _ => Err(format!("unknown expectation '{}' (expected clean or no-errors)", s)),
+        }
+    }
+}
+
+impl Expectation {
+    fn met_by(self, errors: &[SyntaxError]) -> bool {
+        match self {
+            Expectation::Clean => errors.is_empty(),
+            Expectation::NoErrors => errors.iter().all(|e| e.severity != Severity::Error),
+        }
+    }
+}
+
+/// Arguments of `corpus run`.
+#[derive(clap::Args, Debug)]
+pub struct RunArgs {
+    /// Directory to lint, such as a checkout of a known-good repository
+    dir: PathBuf,
+
+    /// What every file must satisfy (clean, no-errors)
+    #[arg(long, default_value = "clean")]
+    expect: Expectation,
+
+    /// Only lint files of this language (java, python, r)
+    #[arg(short, long)]
+    language: Option<String>,
+
+    /// File listing paths, relative to DIR, that are known to fail; one per line, # for comments
+    #[arg(long)]
+    known_failures: Option<PathBuf>,
+
+    /// Number of files to lint in parallel (defaults to the number of CPUs)
+    #[arg(short, long)]
+    jobs: Option<usize>,
+
+    /// Write a JSON summary of the run to this file
+    #[arg(long)]
+    json: Option<PathBuf>,
+
+    /// Write an HTML summary of the run to this file
+    #[arg(long)]
+    html: Option<PathBuf>,
+
+    /// JSON summary of an earlier run to compare the results with
+    #[arg(long)]
+    previous: Option<PathBuf>,
+}
+
+/// How one file fared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    /// The file met the expectation.
+    Pass,
+    /// The file has diagnostics the expectation does not allow.
+    Fail,
+    /// The file could not be linted, for instance because parsing timed out.
+    Error,
+    /// The file failed, as the known-failures manifest says it does.
+    KnownFailure,
+    /// The file passed although the known-failures manifest lists it.
+    Fixed,
+}
+
+impl Status {
+    fn passing(self) -> bool {
+        matches!(self, Status::Pass | Status::Fixed)
+    }
+
+    fn unexpected(self) -> bool {
+        matches!(self, Status::Fail | Status::Error)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileResult {
+    language: String,
+    status: Status,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    diagnostics: Vec<SyntaxError>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Totals {
+    files: usize,
+    passed: usize,
+    failed: usize,
+    errors: usize,
+    known_failures: usize,
+    fixed: usize,
+    duration_seconds: f64,
+}
+
+/// How the results differ from an earlier run's.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Comparison {
+    previous: PathBuf,
+    /// Files that passed before and no longer do.
+    regressions: Vec<String>,
+    /// Files that did not pass before and now do.
+    fixes: Vec<String>,
+    /// Files that failed both times, with different diagnostics.
+    changed: Vec<String>,
+}
+
+/// The JSON summary of a run, as written with `--json` and read with `--previous`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Summary {
+    root: PathBuf,
+    expect: Expectation,
+    totals: Totals,
+    files: BTreeMap<String, FileResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    comparison: Option<Comparison>,
+}
+
+/// The supported files under `dir`, as paths relative to it with `/` separators, sorted.
+/// Hidden files and directories, such as `.git`, are skipped, and symbolic links are not
+/// followed.
+fn collect(dir: &Path, language: Option<&str>) -> io::Result<Vec<(String, &'static str)>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() {
+                let Some(detected) = lintymclintface::detect_language(&path) else { continue };
+                if language.is_some_and(|l| l != detected) {
+                    continue;
+                }
+                let relative = path.strip_prefix(dir).unwrap_or(&path);
+                let relative = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+                files.push((relative, detected));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Reads a known-failures manifest: one relative path per line, ignoring blank lines and
+/// lines starting with `#`.
+fn read_manifest(path: &Path) -> io::Result<BTreeSet<String>> {
+    let contents = fs::read_to_string(path)?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.trim_start_matches("./").to_string())
+        .collect())
+}
+
+/// Lints `path` as `language`, refusing files larger than `max_input_bytes`.
+fn lint_file(
+    path: &Path,
+    language: &str,
+    options: &LintOptions,
+    cache_dir: Option<&Path>,
+    max_input_bytes: usize,
+) -> Result<lintymclintface::LintReport, LinterError> {
+    let size = fs::metadata(path)?.len();
+    if size > max_input_bytes as u64 {
+        return Err(LinterError::Io(format!("File is {} bytes, exceeding the limit of {}", size, max_input_bytes)));
+    }
+    let code = fs::read_to_string(path)?;
+    match cache_dir {
+        Some(dir) => crate::lint_with_disk_cache(dir, language, &code, options),
+        None => lintymclintface::lint_with_options(language, &code, options),
+    }
+}
+
+fn error_message(e: &LinterError) -> String {
+    match e {
+        LinterError::Io(msg)
+        | LinterError::Parse(msg)
+        | LinterError::TreeSitterParseError(msg)
+        | LinterError::UnsupportedLanguage(msg)
+        | LinterError::Timeout(msg)
+        | LinterError::InvalidOption(msg) => msg.clone(),
+    }
+}
+
+/// Whether two results of the same file report the same diagnostics and error.
+fn same_outcome(a: &FileResult, b: &FileResult) -> bool {
+    let key = |d: &SyntaxError| (d.line, d.column, d.rule.clone(), d.message.clone());
+    a.error == b.error && a.diagnostics.iter().map(key).eq(b.diagnostics.iter().map(key))
+}
+
+fn compare(previous_path: &Path, previous: &Summary, files: &BTreeMap<String, FileResult>) -> Comparison {
+    let mut comparison = Comparison { previous: previous_path.to_path_buf(), ..Comparison::default() };
+    for (path, result) in files {
+        let Some(before) = previous.files.get(path) else { continue };
+        match (before.status.passing(), result.status.passing()) {
+            (true, false) => comparison.regressions.push(path.clone()),
+            (false, true) => comparison.fixes.push(path.clone()),
+            (false, false) => {
+                if !same_outcome(before, result) {
+                    comparison.changed.push(path.clone());
+                }
+            }
+            (true, true) => {}
+        }
+    }
+    comparison
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Renders `summary` as a self-contained HTML page listing the files that did not pass.
+fn render_html(summary: &Summary) -> String {
+    let totals = &summary.totals;
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Corpus run: {root}</title>\n\
+         <style>body{{font:14px system-ui,sans-serif;margin:2em}}table{{border-collapse:collapse}}\
+         td,th{{border:1px solid #ddd;padding:.3em .6em;text-align:left;vertical-align:top}}\
+         .fail,.error,.regression{{color:#b00020}}.fixed,.fix{{color:#1a7f37}}.known-failure{{color:#9a6700}}\
+         pre{{margin:0;white-space:pre-wrap}}</style>\n</head>\n<body>\n<h1>Corpus run: {root}</h1>\n\
+         <p>Expecting <code>{expect}</code>: {files} files, {passed} passed, {failed} failed, {errors} errors, \
+         {known} known failures, {fixed} fixed, in {duration:.1} s.</p>\n",
+        root = escape_html(&summary.root.display().to_string()),
+        expect = if summary.expect == Expectation::Clean { "clean" } else { "no-errors" },
+        files = totals.files,
+        passed = totals.passed,
+        failed = totals.failed,
+        errors = totals.errors,
+        known = totals.known_failures,
+        fixed = totals.fixed,
+        duration = totals.duration_seconds,
+    );
+    if let Some(comparison) = &summary.comparison {
+        let _ = writeln!(out, "<h2>Compared with {}</h2>\n<ul>", escape_html(&comparison.previous.display().to_string()));
+        for (class, label, paths) in [
+            ("regression", "Regressed", &comparison.regressions),
+            ("fix", "Fixed", &comparison.fixes),
+            ("changed", "Changed diagnostics", &comparison.changed),
+        ] {
+            for path in paths {
+                let _ = writeln!(out, "<li class=\"{}\">{}: <code>{}</code></li>", class, label, escape_html(path));
+            }
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+    let _ = writeln!(out, "<h2>Files not passing</h2>\n<table>\n<tr><th>File</th><th>Status</th><th>Diagnostics</th></tr>");
+    for (path, result) in summary.files.iter().filter(|(_, r)| !matches!(r.status, Status::Pass)) {
+        let status = serde_json::to_value(result.status).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+        let mut details: Vec<String> = result
+            .diagnostics
+            .iter()
+            .map(|d| escape_html(&lintymclintface::output::format_unix_line(path, d)))
+            .collect();
+        details.extend(result.error.iter().map(|e| escape_html(e)));
+        let _ = writeln!(
+            out,
+            "<tr><td><code>{}</code></td><td class=\"{}\">{}</td><td><pre>{}</pre></td></tr>",
+            escape_html(path),
+            status,
+            status,
+            details.join("\n")
+        );
+    }
+    let _ = writeln!(out, "</table>\n</body>\n</html>");
+    out
+}
+
+/// Runs `corpus run`, printing the files that did not pass and a summary to stdout.
+/// Returns whether every file met the expectation or is a known failure.
+pub fn run(
+    args: &RunArgs,
+    options: &LintOptions,
+    cache_dir: Option<&Path>,
+    max_input_bytes: usize,
+    metrics_file: Option<&Path>,
+) -> io::Result<bool> {
+    let started = Instant::now();
+    if let Some(language) = &args.language {
+        lintymclintface::linters::rules(language).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, error_message(&e)))?;
+    }
+    let files = collect(&args.dir, args.language.as_deref())
+        .map_err(|e| io::Error::new(e.kind(), format!("Cannot read {}: {}", args.dir.display(), e)))?;
+    let known = match &args.known_failures {
+        Some(path) => read_manifest(path).map_err(|e| io::Error::new(e.kind(), format!("Cannot read {}: {}", path.display(), e)))?,
+        None => BTreeSet::new(),
+    };
+    for missing in known.iter().filter(|path| !files.iter().any(|(file, _)| file == *path)) {
+        warn!("Known failure {} is not in the corpus", missing);
+    }
+    let previous = match &args.previous {
+        // A first run has nothing to compare with yet.
+        Some(path) if !path.exists() => None,
+        Some(path) => {
+            let data = fs::read(path)?;
+            let summary: Summary = serde_json::from_slice(&data)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))?;
+            Some((path, summary))
+        }
+        None => None,
+    };
+
+    let jobs = args.jobs.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get())).max(1);
+    info!("Linting {} files under {} with {} jobs", files.len(), args.dir.display(), jobs);
+    let next = AtomicUsize::new(0);
+    let metrics = Mutex::new(metrics_file.map(|_| RunMetrics::new()));
+    let results: Mutex<BTreeMap<String, FileResult>> = Mutex::new(BTreeMap::new());
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(files.len().max(1)) {
+            scope.spawn(|| {
+                while let Some((path, language)) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let result = lint_file(&args.dir.join(path), language, options, cache_dir, max_input_bytes);
+                    if let Some(metrics) = metrics.lock().unwrap().as_mut() {
+                        metrics.record(language, &result);
+                    }
+                    let listed = known.contains(path);
+                    let file = match result {
+                        Ok(report) => {
+                            let status = match (args.expect.met_by(&report.errors), listed) {
+                                (true, false) => Status::Pass,
+                                (true, true) => Status::Fixed,
+                                (false, false) => Status::Fail,
+                                (false, true) => Status::KnownFailure,
+                            };
+                            let diagnostics = if status.passing() { Vec::new() } else { report.errors };
+                            FileResult { language: language.to_string(), status, diagnostics, error: None }
+                        }
+                        Err(e) => FileResult {
+                            language: language.to_string(),
+                            status: if listed { Status::KnownFailure } else { Status::Error },
+                            diagnostics: Vec::new(),
+                            error: Some(error_message(&e)),
+                        },
+                    };
+                    results.lock().unwrap().insert(path.clone(), file);
+                }
+            });
+        }
+    });
+    let files = results.into_inner().unwrap();
+
+    let count = |status: Status| files.values().filter(|r| r.status == status).count();
+    let totals = Totals {
+        files: files.len(),
+        passed: count(Status::Pass),
+        failed: count(Status::Fail),
+        errors: count(Status::Error),
+        known_failures: count(Status::KnownFailure),
+        fixed: count(Status::Fixed),
+        duration_seconds: started.elapsed().as_secs_f64(),
+    };
+    let comparison = previous.as_ref().map(|(path, summary)| compare(path, summary, &files));
+    let summary = Summary { root: args.dir.clone(), expect: args.expect, totals, files, comparison };
+
+    for (path, result) in &summary.files {
+        match result.status {
+            Status::Fail => {
+                println!("FAIL {}", path);
+                for diagnostic in &result.diagnostics {
+                    println!("  {}", lintymclintface::output::format_unix_line(path, diagnostic));
+                }
+            }
+            Status::Error => println!("ERROR {}: {}", path, result.error.as_deref().unwrap_or_default()),
+            Status::Fixed => println!("FIXED {} (passes now; remove it from the known failures)", path),
+            Status::Pass | Status::KnownFailure => {}
+        }
+    }
+    if let Some(comparison) = &summary.comparison {
+        println!(
+            "Compared with {}: {} regressed, {} fixed, {} with changed diagnostics",
+            comparison.previous.display(),
+            comparison.regressions.len(),
+            comparison.fixes.len(),
+            comparison.changed.len()
+        );
+        for (label, paths) in [("regressed", &comparison.regressions), ("fixed", &comparison.fixes), ("changed", &comparison.changed)] {
+            for path in paths {
+                println!("  {} {}", label, path);
+            }
+        }
+    }
+    let totals = &summary.totals;
+    println!(
+        "{} files: {} passed, {} failed, {} errors, {} known failures, {} fixed in {:.1} s",
+        totals.files, totals.passed, totals.failed, totals.errors, totals.known_failures, totals.fixed, totals.duration_seconds
+    );
+
+    if let Some(path) = &args.json {
+        fs::write(path, serde_json::to_vec_pretty(&summary)?)?;
+    }
+    if let Some(path) = &args.html {
+        fs::write(path, render_html(&summary))?;
+    }
+    if let (Some(metrics), Some(path)) = (metrics.into_inner().unwrap(), metrics_file) {
+        metrics.write(path)?;
+    }
+    Ok(!summary.files.values().any(|r| r.status.unexpected()))
+}
@@ -0,0 +1,138 @@
+//! Documents kept open across edits and reparsed incrementally.
+//!
+//! Used for live linting, where a client sends small edits to a large file and expects
+//! diagnostics back after each one without the cost of parsing the whole file again.
+
+use crate::{linters, LintOptions, LintReport, LinterError, SyntaxError};
+use serde::{Deserialize, Serialize};
+use tree_sitter::{InputEdit, Parser, Point, Tree};
+
+/// A position in a document, using the same conventions as [`SyntaxError`]:
+/// 1-based lines and 1-based columns counted in bytes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "service", derive(utoipa::ToSchema))]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Replaces the text between `start` (inclusive) and `end` (exclusive) with `text`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TextEdit {
+    pub start: Position,
+    pub end: Position,
+    pub text: String,
+}
+
+/// The source of one file together with its current syntax tree.
+pub struct Document {
+    language: String,
+    parser: Parser,
+    tree: Tree,
+    text: String,
+    /// Kept for the lifetime of `parser`, which points at its cancellation flag.
+    options: LintOptions,
+}
+
+impl Document {
+    /// Parses `text` as `language` (java, python or r).
+    pub fn open(language: &str, text: String) -> Result<Self, LinterError> {
+        Self::open_with_options(language, text, LintOptions::default())
+    }
+
+    /// Parses `text` as `language`, applying the limits in `options` to every parse and to
+    /// [`Document::report`].
+    pub fn open_with_options(language: &str, text: String, options: LintOptions) -> Result<Self, LinterError> {
+        linters::validate(language, &options)?;
+        let mut parser = linters::parser(language, &options)?;
+        let tree = linters::parse(&mut parser, &text, None, &options)?;
+        Ok(Document { language: language.to_string(), parser, tree, text, options })
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The length in bytes the text would have once `edit` is applied, so that callers can
+    /// refuse edits that would grow it past a limit.
+    pub fn len_after(&self, edit: &TextEdit) -> Result<usize, LinterError> {
+        let start_byte = self.offset(edit.start)?;
+        let old_end_byte = self.offset(edit.end)?;
+        if old_end_byte < start_byte {
+            return Err(LinterError::Parse("Edit ends before it starts".to_string()));
+        }
+        Ok(self.text.len() - (old_end_byte - start_byte) + edit.text.len())
+    }
+
+    /// Applies `edit` to the text and reparses, reusing the unchanged parts of the old tree.
+    pub fn apply(&mut self, edit: &TextEdit) -> Result<(), LinterError> {
+        let start_byte = self.offset(edit.start)?;
+        let old_end_byte = self.offset(edit.end)?;
+        if old_end_byte < start_byte {
+            return Err(LinterError::Parse("Edit ends before it starts".to_string()));
+        }
+        let start_position = self.point(start_byte);
+        let old_end_position = self.point(old_end_byte);
+
+        self.text.replace_range(start_byte..old_end_byte, &edit.text);
+        let new_end_byte = start_byte + edit.text.len();
+        let new_end_position = self.point(new_end_byte);
+
+        self.tree.edit(&InputEdit {
+            start_byte,
+            old_end_byte,
+            new_end_byte,
+            start_position,
+            old_end_position,
+            new_end_position,
+        });
+        self.tree = linters::parse(&mut self.parser, &self.text, Some(&self.tree), &self.options)?;
+        Ok(())
+    }
+
+    /// Lints the current tree.
+    pub fn diagnostics(&self) -> Result<Vec<SyntaxError>, LinterError> {
+        linters::lint_tree(&self.language, &self.tree, &self.text)
+    }
+
+    /// Lints the current tree, selecting and capping the diagnostics as configured in
+    /// [`Document::open_with_options`].
+    pub fn report(&self) -> Result<LintReport, LinterError> {
+        linters::report(&self.language, &self.tree, &self.text, &self.options)
+    }
+
+    /// Converts a [`Position`] to a byte offset into the text.
+    fn offset(&self, position: Position) -> Result<usize, LinterError> {
+        let invalid = || LinterError::Parse(format!("Invalid position {}:{}", position.line, position.column));
+        if position.line == 0 {
+            return Err(invalid());
+        }
+        let line_start = if position.line == 1 {
+            0
+        } else {
+            self.text
+                .match_indices('\n')
+                .nth(position.line - 2)
+                .map(|(i, _)| i + 1)
+                .ok_or_else(invalid)?
+        };
+        let line_end = self.text[line_start..].find('\n').map_or(self.text.len(), |i| line_start + i);
+        let offset = position.column.checked_sub(1).and_then(|c| line_start.checked_add(c)).ok_or_else(invalid)?;
+        if offset > line_end || !self.text.is_char_boundary(offset) {
+            return Err(invalid());
+        }
+        Ok(offset)
+    }
+
+    /// Converts a byte offset to a tree-sitter point (0-based row and byte column).
+    fn point(&self, offset: usize) -> Point {
+        let before = &self.text[..offset];
+        let row = before.matches('\n').count();
+        let column = offset - before.rfind('\n').map_or(0, |i| i + 1);
+        Point { row, column }
+    }
+}
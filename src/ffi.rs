@@ -0,0 +1,170 @@
+//! Stable C ABI for embedding the linters in other runtimes (JNA/Panama, R `.Call`, ...).
+//!
+//! The matching header is `include/lintymclintface.h`. It is generated by `cbindgen`
+//! when building with `--features c-header`.
+//!
+//! Every pointer returned by this module is owned by the caller and must be released
+//! with the matching `lmcf_*_free` function.
+
+use crate::{LinterError, Severity, SyntaxError};
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+/// Severity of a diagnostic.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LmcfSeverity {
+    LmcfWarning = 0,
+    LmcfError = 1,
+}
+
+/// A single diagnostic. `message` and `rule` are NUL-terminated UTF-8 strings.
+#[repr(C)]
+pub struct LmcfDiagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub severity: LmcfSeverity,
+    pub message: *mut c_char,
+    pub rule: *mut c_char,
+}
+
+/// The result of [`lmcf_lint`].
+///
+/// On success `error` is NULL and `diagnostics` points to `len` entries (NULL when `len` is 0).
+/// On failure `error` holds a NUL-terminated description and `len` is 0.
+#[repr(C)]
+pub struct LmcfDiagnosticList {
+    pub diagnostics: *mut LmcfDiagnostic,
+    pub len: usize,
+    pub error: *mut c_char,
+}
+
+/// Lints `len` bytes of UTF-8 `code` written in `language` ("java", "python" or "r").
+///
+/// Returns a list that must be released with [`lmcf_diagnostics_free`]. Never returns NULL.
+///
+/// # Safety
+///
+/// `language` must be a NUL-terminated string and `code` must point to at least `len`
+/// readable bytes (it may be NULL when `len` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn lmcf_lint(language: *const c_char, code: *const c_char, len: usize) -> *mut LmcfDiagnosticList {
+    let list = match guarded(|| lint_raw(language, code, len)) {
+        Ok(errors) => {
+            let diagnostics: Vec<LmcfDiagnostic> = errors.into_iter().map(LmcfDiagnostic::from).collect();
+            let len = diagnostics.len();
+            let diagnostics = if len == 0 {
+                ptr::null_mut()
+            } else {
+                Box::into_raw(diagnostics.into_boxed_slice()) as *mut LmcfDiagnostic
+            };
+            LmcfDiagnosticList { diagnostics, len, error: ptr::null_mut() }
+        }
+        Err(message) => LmcfDiagnosticList { diagnostics: ptr::null_mut(), len: 0, error: to_c_string(&message) },
+    };
+    Box::into_raw(Box::new(list))
+}
+
+/// Releases a list returned by [`lmcf_lint`]. Passing NULL is a no-op.
+///
+/// # Safety
+///
+/// `list` must have been returned by [`lmcf_lint`] and not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn lmcf_diagnostics_free(list: *mut LmcfDiagnosticList) {
+    if list.is_null() {
+        return;
+    }
+    let list = Box::from_raw(list);
+    if !list.diagnostics.is_null() {
+        let diagnostics = Box::from_raw(ptr::slice_from_raw_parts_mut(list.diagnostics, list.len));
+        for diagnostic in diagnostics.iter() {
+            lmcf_string_free(diagnostic.message);
+            lmcf_string_free(diagnostic.rule);
+        }
+    }
+    lmcf_string_free(list.error);
+}
+
+/// Lints like [`lmcf_lint`] but returns the result as a JSON string.
+///
+/// On success the string is the same JSON array the CLI prints; on failure it is an
+/// object of the form `{"error": "..."}`. Release it with [`lmcf_string_free`].
+///
+/// # Safety
+///
+/// Same requirements as [`lmcf_lint`].
+#[no_mangle]
+pub unsafe extern "C" fn lmcf_lint_json(language: *const c_char, code: *const c_char, len: usize) -> *mut c_char {
+    let json = match guarded(|| lint_raw(language, code, len)) {
+        Ok(errors) => serde_json::to_string(&errors),
+        Err(message) => serde_json::to_string(&serde_json::json!({ "error": message })),
+    };
+    to_c_string(&json.unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e)))
+}
+
+/// Releases a string returned by this library. Passing NULL is a no-op.
+///
+/// # Safety
+///
+/// `s` must have been returned by this library and not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn lmcf_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Returns the library version as a static NUL-terminated string. Do not free it.
+#[no_mangle]
+pub extern "C" fn lmcf_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+unsafe fn lint_raw(language: *const c_char, code: *const c_char, len: usize) -> Result<Vec<SyntaxError>, String> {
+    if language.is_null() {
+        return Err("language must not be NULL".to_string());
+    }
+    if code.is_null() && len > 0 {
+        return Err("code must not be NULL".to_string());
+    }
+    let language = CStr::from_ptr(language)
+        .to_str()
+        .map_err(|e| format!("language is not valid UTF-8: {}", e))?;
+    let bytes = if len == 0 { &[][..] } else { std::slice::from_raw_parts(code as *const u8, len) };
+    let code = std::str::from_utf8(bytes).map_err(|e| format!("code is not valid UTF-8: {}", e))?;
+    crate::lint(language, code).map_err(|e| match e {
+        LinterError::Io(msg) => format!("IO Error: {}", msg),
+        LinterError::Parse(msg) => format!("Parse Error: {}", msg),
+        LinterError::TreeSitterParseError(msg) => format!("Tree-sitter Parse Error: {}", msg),
+        LinterError::UnsupportedLanguage(msg) => format!("Unsupported Language Error: {}", msg),
+        LinterError::Timeout(msg) => format!("Timeout Error: {}", msg),
+        LinterError::InvalidOption(msg) => format!("Invalid Option Error: {}", msg),
+    })
+}
+
+/// Runs `f`, turning a panic into an error so that it never unwinds across the C boundary.
+fn guarded<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| Err("internal error: the linter panicked".to_string()))
+}
+
+fn to_c_string(s: &str) -> *mut c_char {
+    // Interior NULs cannot be represented in a C string; drop them rather than fail.
+    CString::new(s.replace('\0', "")).map(CString::into_raw).unwrap_or(ptr::null_mut())
+}
+
+impl From<SyntaxError> for LmcfDiagnostic {
+    fn from(error: SyntaxError) -> Self {
+        LmcfDiagnostic {
+            line: error.line,
+            column: error.column,
+            severity: match error.severity {
+                Severity::Warning => LmcfSeverity::LmcfWarning,
+                Severity::Error => LmcfSeverity::LmcfError,
+            },
+            message: to_c_string(&error.message),
+            rule: to_c_string(&error.rule),
+        }
+    }
+}
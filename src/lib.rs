@@ -1,7 +1,44 @@
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod cache;
+pub mod document;
+pub mod ffi;
+pub mod linters;
+pub mod output;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use linters::{detect_language, lint, lint_timed, lint_with_options, syntax_tree, SUPPORTED_LANGUAGES};
+
+/// How serious a reported diagnostic is.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "service", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// A style or best-practice issue; the code still parses.
+    Warning,
+    /// The code is not syntactically valid.
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
 
 /// Represents a syntax error found by the linter.
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "service", derive(utoipa::ToSchema))]
 pub struct SyntaxError {
     /// The line number where the error occurred (1-based).
     pub line: usize,
@@ -9,6 +46,29 @@ pub struct SyntaxError {
     pub column: usize,
     /// A descriptive message about the error.
     pub message: String,
+    /// How serious the error is.
+    pub severity: Severity,
+    /// The identifier of the check that reported the error, e.g. `java/missing-token`.
+    pub rule: String,
+}
+
+/// A node of the syntax tree returned by [`syntax_tree`].
+///
+/// Only named nodes are included, plus any nodes that tree-sitter inserted or flagged
+/// while recovering from a syntax error.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "service", derive(utoipa::ToSchema))]
+pub struct SyntaxNode {
+    /// The grammar's name for the node, e.g. `class_declaration`.
+    pub kind: String,
+    /// Where the node starts, as a 1-based line and 1-based byte column.
+    pub start: document::Position,
+    /// Where the node ends (exclusive), as a 1-based line and 1-based byte column.
+    pub end: document::Position,
+    /// Whether the node is an `ERROR` node or a token tree-sitter had to insert.
+    pub error: bool,
+    #[cfg_attr(feature = "service", schema(no_recursion))]
+    pub children: Vec<SyntaxNode>,
 }
 
 /// Represents various errors that can occur during the linting process.
@@ -22,6 +82,100 @@ pub enum LinterError {
     TreeSitterParseError(String),
     /// An error indicating that the requested language is not supported by the linter.
     UnsupportedLanguage(String),
+    /// Parsing took longer than the configured timeout and was abandoned.
+    Timeout(String),
+    /// A [`LintOptions`] setting, such as a rule ID, does not apply to the language.
+    InvalidOption(String),
+}
+
+/// A version of a language, such as Java `17` or Python `3.8`, that code has to run on.
+///
+/// Versions are written as `major` or `major.minor`; further components are ignored, so
+/// `4.3.1` targets R 4.3. Java's old `1.8` style is understood as release 8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TargetVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl FromStr for TargetVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid target version '{}' (expected e.g. 17 or 3.8)", s);
+        let mut parts = s.trim().split('.');
+        let major = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        let minor = match parts.next() {
+            Some(p) => p.parse().map_err(|_| invalid())?,
+            None => 0,
+        };
+        Ok(TargetVersion { major, minor })
+    }
+}
+
+impl std::fmt::Display for TargetVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Resource limits and rule selection applied to a single lint.
+#[derive(Debug, Clone, Default)]
+pub struct LintOptions {
+    /// Abandon parsing after this long, reporting [`LinterError::Timeout`].
+    pub timeout: Option<Duration>,
+    /// Report at most this many diagnostics, setting [`LintReport::truncated`] if more were found.
+    pub max_diagnostics: Option<usize>,
+    /// Abandon parsing as soon as this flag becomes non-zero, e.g. when a job is cancelled.
+    pub cancellation: Option<Arc<AtomicUsize>>,
+    /// Report only diagnostics from these rules. Rules are given by ID, e.g.
+    /// `java/missing-token`, or without the language prefix, e.g. `missing-token`.
+    pub rules: Option<Vec<String>>,
+    /// Never report diagnostics from these rules, named as for `rules`.
+    pub disable: Vec<String>,
+    /// Leave out diagnostics less serious than this.
+    pub min_severity: Option<Severity>,
+    /// Report syntax that this version of the language does not support.
+    pub target_version: Option<TargetVersion>,
+}
+
+impl LintOptions {
+    /// Whether `error` passes the rule and severity selection.
+    pub fn selects(&self, error: &SyntaxError) -> bool {
+        let names = |rule: &str| error.rule == rule || error.rule.split_once('/').is_some_and(|(_, name)| name == rule);
+        self.rules.as_ref().is_none_or(|rules| rules.iter().any(|r| names(r)))
+            && !self.disable.iter().any(|r| names(r))
+            && self.min_severity.is_none_or(|min| error.severity >= min)
+    }
+}
+
+/// The diagnostics found by [`lint_with_options`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LintReport {
+    /// The diagnostics, in the order the linter reported them.
+    pub errors: Vec<SyntaxError>,
+    /// Whether `errors` was cut short by [`LintOptions::max_diagnostics`].
+    pub truncated: bool,
+}
+
+/// How long the phases of a lint took, as measured by [`lint_timed`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LintTimings {
+    /// Time spent building the syntax tree.
+    pub parse: Duration,
+    /// Time spent running the rules over the tree.
+    pub check: Duration,
+}
+
+impl LintReport {
+    /// Builds a report of at most `max` diagnostics, marking it truncated if any were dropped.
+    pub fn capped(mut errors: Vec<SyntaxError>, max: Option<usize>) -> Self {
+        let truncated = max.is_some_and(|max| errors.len() > max);
+        if let Some(max) = max {
+            errors.truncate(max);
+        }
+        LintReport { errors, truncated }
+    }
 }
 
 impl From<std::io::Error> for LinterError {
@@ -32,6 +186,6 @@ impl From<std::io::Error> for LinterError {
 
 impl From<LinterError> for std::io::Error {
     fn from(err: LinterError) -> Self {
-        std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", err))
+        std::io::Error::other(format!("{:?}", err))
     }
-}
\ No newline at end of file
+}
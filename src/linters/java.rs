@@ -1,40 +1,19 @@
-use crate::{LinterError, SyntaxError};
-use tree_sitter::{Node, Parser as TreeSitterParser};
+use super::VersionedSyntax;
+use crate::{LinterError, Severity, SyntaxError};
+use tree_sitter::{Node, Tree};
 
-struct Linter {
-    parser: TreeSitterParser,
-}
+/// The checks, run over a parsed tree by [`lint_tree`].
+struct Linter;
 
 impl Linter {
-    fn new() -> Result<Self, LinterError> {
-        let mut parser = TreeSitterParser::new();
-        let language = tree_sitter_java::language();
-        parser
-            .set_language(language)
-            .map_err(|e| LinterError::TreeSitterParseError(format!("Failed to set tree-sitter language for Java: {}", e)))?;
-        Ok(Self { parser })
-    }
-
-    fn lint(&mut self, code: &str) -> Result<Vec<SyntaxError>, LinterError> {
-        let tree = self
-            .parser
-            .parse(code, None)
-            .ok_or_else(|| LinterError::TreeSitterParseError("Tree-sitter failed to parse the entire file. This may indicate highly unusual syntax or an internal tree-sitter issue.".to_string()))?;
-
-        let mut errors = Vec::new();
-        let mut walker = tree.walk();
-        self.find_errors(tree.root_node(), code, &mut errors, &mut walker);
-        Ok(errors)
-    }
-
-    fn find_errors(&self, node: Node, code: &str, errors: &mut Vec<SyntaxError>, cursor: &mut tree_sitter::TreeCursor) {
-        self.check_for_syntax_errors(&node, code, errors);
-        self.check_for_keyword_identifier(&node, code, errors);
-        self.check_for_invalid_constructor(&node, code, errors);
+    fn find_errors(node: Node, code: &str, errors: &mut Vec<SyntaxError>, cursor: &mut tree_sitter::TreeCursor) {
+        Self::check_for_syntax_errors(&node, code, errors);
+        Self::check_for_keyword_identifier(&node, code, errors);
+        Self::check_for_invalid_constructor(&node, code, errors);
 
         if cursor.goto_first_child() {
             loop {
-                self.find_errors(cursor.node(), code, errors, cursor);
+                Self::find_errors(cursor.node(), code, errors, cursor);
                 if !cursor.goto_next_sibling() {
                     break;
                 }
@@ -43,7 +22,7 @@ impl Linter {
         }
     }
 
-    fn check_for_syntax_errors(&self, node: &Node, code: &str, errors: &mut Vec<SyntaxError>) {
+    fn check_for_syntax_errors(node: &Node, code: &str, errors: &mut Vec<SyntaxError>) {
         if node.is_error() {
             let error_text = node.utf8_text(code.as_bytes()).unwrap_or("");
 
@@ -52,6 +31,8 @@ impl Linter {
                 line: start_position.row + 1,
                 column: start_position.column + 1,
                 message: format!("Syntax error near '{}'", error_text),
+                severity: Severity::Error,
+                rule: "java/syntax-error".to_string(),
             });
         } else if node.is_missing() {
             let start_position = node.start_position();
@@ -59,11 +40,13 @@ impl Linter {
                 line: start_position.row + 1,
                 column: start_position.column + 1,
                 message: format!("Missing {}", node.kind()),
+                severity: Severity::Error,
+                rule: "java/missing-token".to_string(),
             });
         }
     }
 
-    fn is_keyword(&self, s: &str) -> bool {
+    fn is_keyword(s: &str) -> bool {
         matches!(s,
             "abstract" | "continue" | "for" | "new" | "switch" | "assert" | "default" | "goto" |
             "package" | "synchronized" | "boolean" | "do" | "if" | "private" | "this" | "break" |
@@ -75,21 +58,23 @@ impl Linter {
         )
     }
 
-    fn check_for_keyword_identifier(&self, node: &Node, code: &str, errors: &mut Vec<SyntaxError>) {
+    fn check_for_keyword_identifier(node: &Node, code: &str, errors: &mut Vec<SyntaxError>) {
         if node.kind() == "identifier" {
             let identifier = node.utf8_text(code.as_bytes()).unwrap();
-            if self.is_keyword(identifier) {
+            if Self::is_keyword(identifier) {
                 let start = node.start_position();
                 errors.push(SyntaxError {
                     line: start.row + 1,
                     column: start.column + 1,
                     message: format!("'{}' is a keyword and cannot be used as an identifier", identifier),
+                    severity: Severity::Error,
+                    rule: "java/keyword-identifier".to_string(),
                 });
             }
         }
     }
 
-    fn check_for_invalid_constructor(&self, node: &Node, code: &str, errors: &mut Vec<SyntaxError>) {
+    fn check_for_invalid_constructor(node: &Node, code: &str, errors: &mut Vec<SyntaxError>) {
         if node.kind() == "constructor_declaration" {
             let mut parent = node.parent();
             let mut is_in_enum = false;
@@ -134,6 +119,8 @@ impl Linter {
                         line: start.row + 1,
                         column: start.column + 1,
                         message: format!("Invalid constructor name '{}'. Constructor name must match the class name '{}'", constructor_name, class_name),
+                        severity: Severity::Error,
+                        rule: "java/invalid-constructor".to_string(),
                     });
                 }
             }
@@ -143,6 +130,37 @@ impl Linter {
 
 /// Lints the given Java code and returns a list of syntax errors.
 pub fn lint(code: &str) -> Result<Vec<SyntaxError>, LinterError> {
-    let mut linter = Linter::new()?;
-    linter.lint(code)
-}
\ No newline at end of file
+    super::lint("java", code)
+}
+
+/// Returns the tree-sitter grammar used for Java.
+pub fn language() -> tree_sitter::Language {
+    tree_sitter_java::language()
+}
+
+/// The rule IDs this linter can report.
+pub const RULES: &[&str] = &[
+    "java/syntax-error",
+    "java/missing-token",
+    "java/keyword-identifier",
+    "java/invalid-constructor",
+    "java/target-version",
+];
+
+/// Syntax the grammar accepts that older Java releases do not.
+pub(crate) const VERSIONED_SYNTAX: &[VersionedSyntax] = &[
+    VersionedSyntax::since("lambda_expression", None, "A lambda expression", 8, 0),
+    VersionedSyntax::since("module_declaration", None, "A module declaration", 9, 0),
+    VersionedSyntax::since("type_identifier", Some("var"), "The `var` type", 10, 0),
+    VersionedSyntax::since("switch_expression", None, "A switch expression", 14, 0),
+    VersionedSyntax::since("record_declaration", None, "A record declaration", 16, 0),
+    VersionedSyntax::since("record_pattern", None, "A record pattern", 21, 0),
+];
+
+/// Runs the Java checks over an already parsed tree of `code`.
+pub fn lint_tree(tree: &Tree, code: &str) -> Vec<SyntaxError> {
+    let mut errors = Vec::new();
+    let mut walker = tree.walk();
+    Linter::find_errors(tree.root_node(), code, &mut errors, &mut walker);
+    errors
+}
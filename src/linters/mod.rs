@@ -1,3 +1,226 @@
+use crate::document::Position;
+use crate::{LintOptions, LintReport, LintTimings, LinterError, Severity, SyntaxError, SyntaxNode, TargetVersion};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+use tree_sitter::{Node, Parser, Tree};
+
 pub mod java;
 pub mod python;
-pub mod r;
\ No newline at end of file
+pub mod r;
+
+/// The languages that can be passed to [`lint`].
+pub const SUPPORTED_LANGUAGES: &[&str] = &["java", "python", "r"];
+
+/// Lints `code` with the linter for `language` (java, python or r), with the default
+/// [`LintOptions`]: every rule and no limits.
+pub fn lint(language: &str, code: &str) -> Result<Vec<SyntaxError>, LinterError> {
+    lint_with_options(language, code, &LintOptions::default()).map(|report| report.errors)
+}
+
+/// Lints `code` like [`lint`], applying the limits and rule selection in `options`.
+pub fn lint_with_options(language: &str, code: &str, options: &LintOptions) -> Result<LintReport, LinterError> {
+    lint_timed(language, code, options).map(|(report, _)| report)
+}
+
+/// Lints `code` like [`lint_with_options`], also returning how long parsing and checking took.
+pub fn lint_timed(language: &str, code: &str, options: &LintOptions) -> Result<(LintReport, LintTimings), LinterError> {
+    let _span = tracing::info_span!("lint", language, bytes = code.len()).entered();
+    validate(language, options)?;
+    let mut parser = parser(language, options)?;
+    let start = Instant::now();
+    let tree = parse(&mut parser, code, None, options)?;
+    let parsed = Instant::now();
+    let report = report(language, &tree, code, options)?;
+    Ok((report, LintTimings { parse: parsed - start, check: parsed.elapsed() }))
+}
+
+/// Checks that the rules named in `options` exist for `language`.
+pub fn validate(language: &str, options: &LintOptions) -> Result<(), LinterError> {
+    let known = rules(language)?;
+    let named = options.rules.iter().flatten().chain(&options.disable);
+    for rule in named {
+        if !known.iter().any(|id| id == rule || id.split_once('/').is_some_and(|(_, name)| name == rule)) {
+            return Err(LinterError::InvalidOption(format!("Unknown rule '{}' for {}", rule, language)));
+        }
+    }
+    Ok(())
+}
+
+/// Runs the checks for `language` over a tree parsed from `code`, including those for
+/// `options.target_version`, and keeps the diagnostics `options` selects.
+pub(crate) fn report(language: &str, tree: &Tree, code: &str, options: &LintOptions) -> Result<LintReport, LinterError> {
+    let mut errors = tracing::info_span!("rule_pass", pass = "checks").in_scope(|| lint_tree(language, tree, code))?;
+    if let Some(target) = options.target_version {
+        let span = tracing::info_span!("rule_pass", pass = "target-version", %target);
+        errors.extend(span.in_scope(|| check_target(language, tree, code, target))?);
+    }
+    errors.retain(|error| options.selects(error));
+    Ok(LintReport::capped(errors, options.max_diagnostics))
+}
+
+/// Syntax that only some versions of a language accept, although the grammar parses it.
+pub(crate) struct VersionedSyntax {
+    /// The tree-sitter node kind.
+    kind: &'static str,
+    /// The text the node must have, for syntax told apart by a contextual keyword.
+    text: Option<&'static str>,
+    /// How diagnostics refer to the syntax.
+    name: &'static str,
+    /// The first version that accepts the syntax, if it was added later.
+    since: Option<TargetVersion>,
+    /// The first version that no longer accepts the syntax, if it was removed.
+    until: Option<TargetVersion>,
+}
+
+impl VersionedSyntax {
+    pub(crate) const fn since(kind: &'static str, text: Option<&'static str>, name: &'static str, major: u32, minor: u32) -> Self {
+        VersionedSyntax { kind, text, name, since: Some(TargetVersion { major, minor }), until: None }
+    }
+
+    pub(crate) const fn until(kind: &'static str, name: &'static str, major: u32, minor: u32) -> Self {
+        VersionedSyntax { kind, text: None, name, since: None, until: Some(TargetVersion { major, minor }) }
+    }
+}
+
+/// Reports the syntax in `tree` that `target` does not support, under `<language>/target-version`.
+fn check_target(language: &str, tree: &Tree, code: &str, target: TargetVersion) -> Result<Vec<SyntaxError>, LinterError> {
+    let (syntax, name) = match language {
+        "java" => (java::VERSIONED_SYNTAX, "Java"),
+        "python" => (python::VERSIONED_SYNTAX, "Python"),
+        "r" => (r::VERSIONED_SYNTAX, "R"),
+        _ => return Err(LinterError::UnsupportedLanguage(language.to_string())),
+    };
+    // Java releases are numbered 8, 9, ... but were written 1.8 before that.
+    let java = language == "java";
+    let target = match target {
+        TargetVersion { major: 1, minor } if java => TargetVersion { major: minor, minor: 0 },
+        _ => target,
+    };
+    let version = |v: TargetVersion| if java { v.major.to_string() } else { v.to_string() };
+
+    let mut errors = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor).collect::<Vec<_>>().into_iter().rev());
+        let Some(found) = syntax
+            .iter()
+            .find(|s| s.kind == node.kind() && s.text.is_none_or(|text| node.utf8_text(code.as_bytes()) == Ok(text)))
+        else {
+            continue;
+        };
+        let message = match (found.since, found.until) {
+            (Some(since), _) if target < since => format!("{} requires {} {} or later", found.name, name, version(since)),
+            (_, Some(until)) if target >= until => format!("{} is not supported since {} {}", found.name, name, version(until)),
+            _ => continue,
+        };
+        let start = node.start_position();
+        errors.push(SyntaxError {
+            line: start.row + 1,
+            column: start.column + 1,
+            message: format!("{} (targeting {} {})", message, name, version(target)),
+            severity: Severity::Error,
+            rule: format!("{}/target-version", language),
+        });
+    }
+    Ok(errors)
+}
+
+/// Parses `code` as `language` and returns its syntax tree, applying the timeout in `options`.
+pub fn syntax_tree(language: &str, code: &str, options: &LintOptions) -> Result<SyntaxNode, LinterError> {
+    let mut parser = parser(language, options)?;
+    let tree = parse(&mut parser, code, None, options)?;
+    Ok(syntax_node(tree.root_node()))
+}
+
+fn syntax_node(node: Node) -> SyntaxNode {
+    let position = |p: tree_sitter::Point| Position { line: p.row + 1, column: p.column + 1 };
+    let mut cursor = node.walk();
+    let children = node
+        .children(&mut cursor)
+        .filter(|child| child.is_named() || child.is_error() || child.is_missing())
+        .map(syntax_node)
+        .collect();
+    SyntaxNode {
+        kind: node.kind().to_string(),
+        start: position(node.start_position()),
+        end: position(node.end_position()),
+        error: node.is_error() || node.is_missing(),
+        children,
+    }
+}
+
+/// Creates a parser for `language` with the timeout and cancellation flag from `options` applied.
+///
+/// The parser holds a raw pointer to the cancellation flag, so callers must keep `options`
+/// alive for as long as they use the parser.
+pub(crate) fn parser(language: &str, options: &LintOptions) -> Result<Parser, LinterError> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(grammar(language)?)
+        .map_err(|e| LinterError::TreeSitterParseError(format!("Failed to set tree-sitter language for {}: {}", language, e)))?;
+    if let Some(timeout) = options.timeout {
+        parser.set_timeout_micros(timeout.as_micros().max(1) as u64);
+    }
+    // SAFETY: callers keep `options`, and with it the flag, alive while the parser is in use.
+    unsafe { parser.set_cancellation_flag(options.cancellation.as_deref()) };
+    Ok(parser)
+}
+
+/// Parses `code`, reusing `old_tree` for incremental reparsing if given.
+///
+/// Tree-sitter only gives up on a parse when it times out or is cancelled through the flag
+/// in `options`, so a failure with a timeout configured is reported as [`LinterError::Timeout`].
+pub fn parse(parser: &mut Parser, code: &str, old_tree: Option<&Tree>, options: &LintOptions) -> Result<Tree, LinterError> {
+    let _span = tracing::info_span!("parse", incremental = old_tree.is_some()).entered();
+    parser.parse(code, old_tree).ok_or_else(|| {
+        parser.reset();
+        let cancelled = options.cancellation.as_ref().is_some_and(|flag| flag.load(Ordering::Relaxed) != 0);
+        match options.timeout {
+            _ if cancelled => LinterError::Parse("Parsing was cancelled".to_string()),
+            Some(timeout) => LinterError::Timeout(format!("Parsing timed out after {} ms", timeout.as_millis())),
+            None => LinterError::TreeSitterParseError("Tree-sitter failed to parse the code".to_string()),
+        }
+    })
+}
+
+/// Returns the tree-sitter grammar for `language`.
+pub fn grammar(language: &str) -> Result<tree_sitter::Language, LinterError> {
+    match language {
+        "java" => Ok(java::language()),
+        "python" => Ok(python::language()),
+        "r" => Ok(r::language()),
+        _ => Err(LinterError::UnsupportedLanguage(language.to_string())),
+    }
+}
+
+/// Returns the IDs of the rules checked for `language`.
+pub fn rules(language: &str) -> Result<&'static [&'static str], LinterError> {
+    match language {
+        "java" => Ok(java::RULES),
+        "python" => Ok(python::RULES),
+        "r" => Ok(r::RULES),
+        _ => Err(LinterError::UnsupportedLanguage(language.to_string())),
+    }
+}
+
+/// Runs the checks for `language` over a tree already parsed from `code`.
+pub fn lint_tree(language: &str, tree: &Tree, code: &str) -> Result<Vec<SyntaxError>, LinterError> {
+    match language {
+        "java" => Ok(java::lint_tree(tree, code)),
+        "python" => Ok(python::lint_tree(tree, code)),
+        "r" => Ok(r::lint_tree(tree, code)),
+        _ => Err(LinterError::UnsupportedLanguage(language.to_string())),
+    }
+}
+
+/// Guesses the language of a file from its extension.
+pub fn detect_language(path: &Path) -> Option<&'static str> {
+    match path.extension()?.to_str()? {
+        "java" => Some("java"),
+        "py" => Some("python"),
+        "R" | "r" => Some("r"),
+        _ => None,
+    }
+}
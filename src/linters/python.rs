@@ -1,48 +1,22 @@
-use crate::{LinterError, SyntaxError};
-use tree_sitter::{Node, Parser as TreeSitterParser};
+use super::VersionedSyntax;
+use crate::{LinterError, Severity, SyntaxError};
+use tree_sitter::{Node, Tree};
 use tracing::{debug, error};
 
-struct Linter {
-    parser: TreeSitterParser,
-}
+/// The checks, run over a parsed tree by [`lint_tree`].
+struct Linter;
 
 impl Linter {
-    fn new() -> Result<Self, LinterError> {
-        let mut parser = TreeSitterParser::new();
-        let language = tree_sitter_python::language();
-        parser
-            .set_language(language)
-            .map_err(|e| LinterError::Parse(e.to_string()))?;
-        Ok(Self { parser })
-    }
-
-    fn lint(&mut self, code: &str) -> Result<Vec<SyntaxError>, LinterError> {
-        debug!("Attempting to parse code...");
-        let tree = self
-            .parser
-            .parse(code, None)
-            .ok_or_else(|| {
-                error!("Failed to parse code into a syntax tree.");
-                LinterError::Parse("Failed to parse code".to_string())
-            })?;
-        debug!("Code parsed successfully. Traversing syntax tree...");
-        let mut errors = Vec::new();
-        let mut walker = tree.walk();
-        self.find_errors(tree.root_node(), code, &mut errors, &mut walker);
-        debug!("Finished traversing syntax tree. Found {} errors.", errors.len());
-        Ok(errors)
-    }
-
-    fn find_errors(&self, node: Node, code: &str, errors: &mut Vec<SyntaxError>, cursor: &mut tree_sitter::TreeCursor) {
+    fn find_errors(node: Node, code: &str, errors: &mut Vec<SyntaxError>, cursor: &mut tree_sitter::TreeCursor) {
         debug!("Visiting node: kind={}, text='{}', is_error={}, is_missing={}", 
                node.kind(), node.utf8_text(code.as_bytes()).unwrap_or(""), node.is_error(), node.is_missing());
 
-        self.check_for_syntax_errors(&node, code, errors);
-        self.check_for_print_statements(&node, code, errors);
+        Self::check_for_syntax_errors(&node, code, errors);
+        Self::check_for_print_statements(&node, code, errors);
 
         if cursor.goto_first_child() {
             loop {
-                self.find_errors(cursor.node(), code, errors, cursor);
+                Self::find_errors(cursor.node(), code, errors, cursor);
                 if !cursor.goto_next_sibling() {
                     break;
                 }
@@ -51,7 +25,7 @@ impl Linter {
         }
     }
 
-    fn check_for_print_statements(&self, node: &Node, code: &str, errors: &mut Vec<SyntaxError>) {
+    fn check_for_print_statements(node: &Node, code: &str, errors: &mut Vec<SyntaxError>) {
         if node.kind() == "call" {
             if let Some(function_node) = node.child_by_field_name("function") {
                 let function_name = function_node.utf8_text(code.as_bytes()).unwrap();
@@ -62,13 +36,15 @@ impl Linter {
                         line: start.row + 1,
                         column: start.column + 1,
                         message: "Use of print statements is discouraged".to_string(),
+                        severity: Severity::Warning,
+                        rule: "python/print-statement".to_string(),
                     });
                 }
             }
         }
     }
 
-    fn check_for_syntax_errors(&self, node: &Node, code: &str, errors: &mut Vec<SyntaxError>) {
+    fn check_for_syntax_errors(node: &Node, code: &str, errors: &mut Vec<SyntaxError>) {
         if node.is_error() {
             let error_text = node.utf8_text(code.as_bytes()).unwrap_or("");
             let start_position = node.start_position();
@@ -78,6 +54,8 @@ impl Linter {
                 line: start_position.row + 1,
                 column: start_position.column + 1,
                 message: format!("Syntax error near '{}'", error_text),
+                severity: Severity::Error,
+                rule: "python/syntax-error".to_string(),
             });
         } else if node.is_missing() {
             let start_position = node.start_position();
@@ -87,6 +65,8 @@ impl Linter {
                 line: start_position.row + 1,
                 column: start_position.column + 1,
                 message: format!("Missing {}", node.kind()),
+                severity: Severity::Error,
+                rule: "python/missing-token".to_string(),
             });
         }
     }
@@ -94,6 +74,30 @@ impl Linter {
 
 /// Lints the given Python code and returns a list of syntax errors.
 pub fn lint(code: &str) -> Result<Vec<SyntaxError>, LinterError> {
-    let mut linter = Linter::new()?;
-    linter.lint(code)
-}
\ No newline at end of file
+    super::lint("python", code)
+}
+
+/// Returns the tree-sitter grammar used for Python.
+pub fn language() -> tree_sitter::Language {
+    tree_sitter_python::language()
+}
+
+/// The rule IDs this linter can report.
+pub const RULES: &[&str] = &["python/syntax-error", "python/missing-token", "python/print-statement", "python/target-version"];
+
+/// Syntax the grammar accepts that only some Python versions do.
+pub(crate) const VERSIONED_SYNTAX: &[VersionedSyntax] = &[
+    VersionedSyntax::until("print_statement", "The print statement", 3, 0),
+    VersionedSyntax::until("exec_statement", "The exec statement", 3, 0),
+    VersionedSyntax::since("interpolation", None, "An f-string", 3, 6),
+    VersionedSyntax::since("named_expression", None, "An assignment expression", 3, 8),
+    VersionedSyntax::since("match_statement", None, "A match statement", 3, 10),
+];
+
+/// Runs the Python checks over an already parsed tree of `code`.
+pub fn lint_tree(tree: &Tree, code: &str) -> Vec<SyntaxError> {
+    let mut errors = Vec::new();
+    let mut walker = tree.walk();
+    Linter::find_errors(tree.root_node(), code, &mut errors, &mut walker);
+    errors
+}
@@ -1,38 +1,18 @@
-use crate::{LinterError, SyntaxError};
-use tree_sitter::{Node, Parser as TreeSitterParser};
+use super::VersionedSyntax;
+use crate::{LinterError, Severity, SyntaxError};
+use tree_sitter::{Node, Tree};
 
-struct Linter {
-    parser: TreeSitterParser,
-}
+/// The checks, run over a parsed tree by [`lint_tree`].
+struct Linter;
 
 impl Linter {
-    fn new() -> Result<Self, LinterError> {
-        let mut parser = TreeSitterParser::new();
-        let language = tree_sitter_r::language();
-        parser
-            .set_language(language)
-            .map_err(|e| LinterError::Parse(e.to_string()))?;
-        Ok(Self { parser })
-    }
-
-    fn lint(&mut self, code: &str) -> Result<Vec<SyntaxError>, LinterError> {
-        let tree = self
-            .parser
-            .parse(code, None)
-            .ok_or_else(|| LinterError::Parse("Failed to parse code".to_string()))?;
-        let mut errors = Vec::new();
-        let mut walker = tree.walk();
-        self.find_errors(tree.root_node(), code, &mut errors, &mut walker);
-        Ok(errors)
-    }
-
-    fn find_errors(&self, node: Node, code: &str, errors: &mut Vec<SyntaxError>, cursor: &mut tree_sitter::TreeCursor) {
-        self.check_for_syntax_errors(&node, code, errors);
-        self.check_for_arrow_assignment(&node, code, errors);
+    fn find_errors(node: Node, code: &str, errors: &mut Vec<SyntaxError>, cursor: &mut tree_sitter::TreeCursor) {
+        Self::check_for_syntax_errors(&node, code, errors);
+        Self::check_for_arrow_assignment(&node, code, errors);
 
         if cursor.goto_first_child() {
             loop {
-                self.find_errors(cursor.node(), code, errors, cursor);
+                Self::find_errors(cursor.node(), code, errors, cursor);
                 if !cursor.goto_next_sibling() {
                     break;
                 }
@@ -41,18 +21,20 @@ impl Linter {
         }
     }
 
-    fn check_for_arrow_assignment(&self, node: &Node, _code: &str, errors: &mut Vec<SyntaxError>) {
+    fn check_for_arrow_assignment(node: &Node, _code: &str, errors: &mut Vec<SyntaxError>) {
         if node.kind() == "<-" {
             let start = node.start_position();
             errors.push(SyntaxError {
                 line: start.row + 1,
                 column: start.column + 1,
                 message: "Use '=' for assignment instead of '<-'".to_string(),
+                severity: Severity::Warning,
+                rule: "r/arrow-assignment".to_string(),
             });
         }
     }
 
-    fn check_for_syntax_errors(&self, node: &Node, code: &str, errors: &mut Vec<SyntaxError>) {
+    fn check_for_syntax_errors(node: &Node, code: &str, errors: &mut Vec<SyntaxError>) {
         if node.is_error() {
             let error_text = node.utf8_text(code.as_bytes()).unwrap_or("");
             let start_position = node.start_position();
@@ -60,6 +42,8 @@ impl Linter {
                 line: start_position.row + 1,
                 column: start_position.column + 1,
                 message: format!("Syntax error near '{}'", error_text),
+                severity: Severity::Error,
+                rule: "r/syntax-error".to_string(),
             });
         } else if node.is_missing() {
             let start_position = node.start_position();
@@ -67,6 +51,8 @@ impl Linter {
                 line: start_position.row + 1,
                 column: start_position.column + 1,
                 message: format!("Missing {}", node.kind()),
+                severity: Severity::Error,
+                rule: "r/missing-token".to_string(),
             });
         }
     }
@@ -74,6 +60,27 @@ impl Linter {
 
 /// Lints the given R code and returns a list of syntax errors.
 pub fn lint(code: &str) -> Result<Vec<SyntaxError>, LinterError> {
-    let mut linter = Linter::new()?;
-    linter.lint(code)
-}
\ No newline at end of file
+    super::lint("r", code)
+}
+
+/// Returns the tree-sitter grammar used for R.
+pub fn language() -> tree_sitter::Language {
+    tree_sitter_r::language()
+}
+
+/// The rule IDs this linter can report.
+pub const RULES: &[&str] = &["r/syntax-error", "r/missing-token", "r/arrow-assignment", "r/target-version"];
+
+/// Syntax the grammar accepts that older R versions do not.
+pub(crate) const VERSIONED_SYNTAX: &[VersionedSyntax] = &[
+    VersionedSyntax::since("pipe", None, "The native pipe `|>`", 4, 1),
+    VersionedSyntax::since("lambda_function", None, "The lambda shorthand `\\(x)`", 4, 1),
+];
+
+/// Runs the R checks over an already parsed tree of `code`.
+pub fn lint_tree(tree: &Tree, code: &str) -> Vec<SyntaxError> {
+    let mut errors = Vec::new();
+    let mut walker = tree.walk();
+    Linter::find_errors(tree.root_node(), code, &mut errors, &mut walker);
+    errors
+}
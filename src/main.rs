@@ -1,112 +1,26 @@
-use actix_web::{web, App, HttpServer, Responder};
 use std::io::IsTerminal;
-use serde::Deserialize;
-use clap::{Parser, CommandFactory};
+use clap::{Parser, Subcommand, CommandFactory};
 use std::fs;
-use tracing::{info, error};
-use tracing_subscriber::{EnvFilter, FmtSubscriber};
+use tracing::{debug, info, warn, error};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
 
-use prometheus::{Encoder, TextEncoder, Gauge, Counter, Opts, Registry, IntCounterVec};
-use lazy_static::lazy_static;
-use std::time::Instant;
+use lintymclintface::{LintOptions, LintReport, SyntaxError, LinterError, Severity}; // Import from the library
+use lintymclintface::cache::{CacheKey, DiskCache};
+use lintymclintface::output::{self, OutputFormat};
 
-mod linters;
-use lintymclintface::{SyntaxError, LinterError}; // Import from the library
-
-#[derive(Deserialize)]
-struct LintRequest {
-    language: String,
-    code: String,
-}
-
-// --- Prometheus Metrics --- 
-lazy_static! {
-    pub static ref REGISTRY: Registry = Registry::new();
-    pub static ref LINT_REQUESTS_TOTAL: Counter = Counter::new(
-        "lint_requests_total",
-        "Total number of linting requests."
-    ).unwrap();
-    pub static ref LINT_REQUESTS_BY_LANGUAGE: IntCounterVec = IntCounterVec::new(
-        Opts::new("lint_requests_by_language", "Total number of linting requests by language."),
-        &["language"]
-    ).unwrap();
-    pub static ref LINT_DURATION_SECONDS: Gauge = Gauge::new(
-        "lint_duration_seconds",
-        "Duration of linting requests in seconds."
-    ).unwrap();
-    pub static ref LINT_ERRORS_TOTAL: Counter = Counter::new(
-        "lint_errors_total",
-        "Total number of linting errors found."
-    ).unwrap();
-    pub static ref LINT_ERRORS_BY_LANGUAGE: IntCounterVec = IntCounterVec::new(
-        Opts::new("lint_errors_by_language", "Total number of linting errors by language."),
-        &["language"]
-    ).unwrap();
-    pub static ref LINT_LAST_FILE_ERRORS: Gauge = Gauge::new(
-        "lint_last_file_errors",
-        "Number of errors in the last processed file."
-    ).unwrap();
-}
-
-fn register_metrics() {
-    REGISTRY.register(Box::new(LINT_REQUESTS_TOTAL.clone())).unwrap();
-    REGISTRY.register(Box::new(LINT_REQUESTS_BY_LANGUAGE.clone())).unwrap();
-    REGISTRY.register(Box::new(LINT_DURATION_SECONDS.clone())).unwrap();
-    REGISTRY.register(Box::new(LINT_ERRORS_TOTAL.clone())).unwrap();
-    REGISTRY.register(Box::new(LINT_ERRORS_BY_LANGUAGE.clone())).unwrap();
-    REGISTRY.register(Box::new(LINT_LAST_FILE_ERRORS.clone())).unwrap();
-}
-
-async fn lint_service(req: web::Json<LintRequest>) -> impl Responder {
-    info!("Received lint request for language: {}", req.language);
-    LINT_REQUESTS_TOTAL.inc();
-    LINT_REQUESTS_BY_LANGUAGE.with_label_values(&[&req.language]).inc();
-
-    let start_time = Instant::now();
-
-    let result = match req.language.as_str() {
-        "java" => linters::java::lint(&req.code),
-        "python" => linters::python::lint(&req.code),
-        "r" => linters::r::lint(&req.code),
-        _ => Err(LinterError::UnsupportedLanguage(req.language.clone())),
-    };
-
-    let duration = start_time.elapsed().as_secs_f64();
-    LINT_DURATION_SECONDS.set(duration);
-
-    match result {
-        Ok(errors) => {
-            LINT_ERRORS_TOTAL.inc_by(errors.len() as f64);
-            LINT_ERRORS_BY_LANGUAGE.with_label_values(&[&req.language]).inc_by(errors.len() as u64);
-            LINT_LAST_FILE_ERRORS.set(errors.len() as f64);
-            web::Json(errors)
-        },
-        Err(e) => {
-            LINT_ERRORS_TOTAL.inc(); // Increment for linter errors themselves
-            let error = match e {
-                LinterError::Io(msg) => SyntaxError { line: 0, column: 0, message: msg },
-                LinterError::Parse(msg) => SyntaxError { line: 0, column: 0, message: msg },
-                LinterError::TreeSitterParseError(msg) => SyntaxError { line: 0, column: 0, message: msg },
-                LinterError::UnsupportedLanguage(msg) => SyntaxError { line: 0, column: 0, message: format!("Unsupported language: {}", msg) },
-            };
-            web::Json(vec![error])
-        }
-    }
-}
-
-// New handler for /metrics endpoint
-async fn metrics() -> impl Responder {
-    let mut buffer = Vec::new();
-    let encoder = TextEncoder::new();
-    let metric_families = REGISTRY.gather();
-    encoder.encode(&metric_families, &mut buffer).unwrap();
-    String::from_utf8(buffer).unwrap()
-}
+mod corpus;
+#[cfg(feature = "service")]
+mod service;
+mod textfile;
 
 /// Command-line arguments for the lintymclintface application.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Language to lint (java, python, r)
     #[arg(short, long)]
     language: Option<String>,
@@ -115,34 +29,245 @@ struct Cli {
     #[arg(short, long)]
     file: Option<String>,
 
+    /// Output format for lint results (json, unix, ndjson)
+    #[arg(long, default_value = "json")]
+    format: OutputFormat,
+
+    /// Maximum size in bytes of a file to lint, of a /lint request body or live-lint document, or of
+    /// any one file in a batch, archive or job
+    #[arg(long, default_value_t = 16 * 1024 * 1024, env = "LINT_MAX_INPUT_BYTES", global = true)]
+    max_input_bytes: usize,
+
+    /// Abandon parsing a file after this many milliseconds (0 for no limit)
+    #[arg(long, default_value_t = 10_000, env = "LINT_PARSE_TIMEOUT_MS", global = true)]
+    parse_timeout_ms: u64,
+
+    /// Report at most this many diagnostics per file
+    #[arg(long, env = "LINT_MAX_DIAGNOSTICS", global = true)]
+    max_diagnostics: Option<usize>,
+
+    /// Write Prometheus metrics for the run to this file, for node_exporter's textfile collector
+    /// (give it a .prom extension)
+    #[arg(long, env = "LINT_METRICS_FILE", global = true)]
+    metrics_file: Option<std::path::PathBuf>,
+
+    /// Format of log lines (text, json); JSON puts each event on one line with its span fields
+    #[arg(long, default_value = "text", env = "LINT_LOG_FORMAT", global = true)]
+    log_format: LogFormat,
+
+    /// File to append log lines to instead of stderr
+    #[arg(long, env = "LINT_LOG_FILE", global = true)]
+    log_file: Option<std::path::PathBuf>,
+
+    /// Directory in which to cache lint results, so that unchanged files are not linted again
+    #[arg(long, env = "LINT_CACHE_DIR", global = true)]
+    cache_dir: Option<std::path::PathBuf>,
+
     /// Start as a web service
+    #[cfg(feature = "service")]
     #[arg(short, long)]
     service: bool,
 
+    /// Address of the interface the web service listens on, e.g. 127.0.0.1 or ::1
+    #[cfg(feature = "service")]
+    #[arg(long, default_value = "0.0.0.0", env = "LINT_BIND")]
+    bind: std::net::IpAddr,
+
     /// Port to listen on for the web service
+    #[cfg(feature = "service")]
     #[arg(long, default_value_t = 8080, env = "LINT_SERVER_PORT")]
     port: u16,
+
+    /// Unix domain socket to serve on instead of a TCP port, so that the web service is not
+    /// reachable over the network
+    #[cfg(feature = "service")]
+    #[arg(long, env = "LINT_UNIX_SOCKET")]
+    unix_socket: Option<std::path::PathBuf>,
+
+    /// PEM file with the TLS certificate chain for the web service and gRPC API (reloaded when it changes)
+    #[cfg(feature = "tls")]
+    #[arg(long, env = "LINT_TLS_CERT", requires = "tls_key")]
+    tls_cert: Option<std::path::PathBuf>,
+
+    /// PEM file with the private key for --tls-cert (reloaded when it changes)
+    #[cfg(feature = "tls")]
+    #[arg(long, env = "LINT_TLS_KEY", requires = "tls_cert")]
+    tls_key: Option<std::path::PathBuf>,
+
+    /// Maximum number of files accepted in one batch lint request
+    #[cfg(feature = "service")]
+    #[arg(long, default_value_t = 1000, env = "LINT_MAX_BATCH_SIZE")]
+    max_batch_size: usize,
+
+    /// Maximum size in bytes of a batch, archive or job request body, or of a gRPC message
+    #[cfg(feature = "service")]
+    #[arg(long, default_value_t = 64 * 1024 * 1024, env = "LINT_MAX_BULK_BYTES")]
+    max_bulk_bytes: usize,
+
+    /// Maximum total decompressed size in bytes of an archive sent for linting
+    #[cfg(feature = "service")]
+    #[arg(long, default_value_t = 256 * 1024 * 1024, env = "LINT_MAX_ARCHIVE_BYTES")]
+    max_archive_bytes: u64,
+
+    /// Maximum number of files in an archive sent for linting
+    #[cfg(feature = "service")]
+    #[arg(long, default_value_t = 10_000, env = "LINT_MAX_ARCHIVE_FILES")]
+    max_archive_files: usize,
+
+    /// How long the results of a finished lint job are kept, in seconds
+    #[cfg(feature = "service")]
+    #[arg(long, default_value_t = 3600, env = "LINT_JOB_TTL_SECS")]
+    job_ttl_secs: u64,
+
+    /// Maximum number of lint jobs queued or running at once; further jobs are refused with 503
+    #[cfg(feature = "service")]
+    #[arg(long, default_value_t = 16, env = "LINT_MAX_JOBS")]
+    max_jobs: usize,
+
+    /// File of "<client> <key>" lines; when given, service requests must present one of the keys
+    #[cfg(feature = "service")]
+    #[arg(long, env = "LINT_API_KEYS_FILE")]
+    api_keys_file: Option<std::path::PathBuf>,
+
+    /// Requests per second allowed for each service client (unlimited if not given)
+    #[cfg(feature = "service")]
+    #[arg(long, env = "LINT_RATE_LIMIT")]
+    rate_limit: Option<f64>,
+
+    /// Number of requests a service client may burst before the rate limit applies
+    #[cfg(feature = "service")]
+    #[arg(long, default_value_t = 10, env = "LINT_RATE_LIMIT_BURST")]
+    rate_limit_burst: u32,
+
+    /// Seconds to let in-flight requests finish after SIGTERM before the service exits
+    #[cfg(feature = "service")]
+    #[arg(long, default_value_t = 30, env = "LINT_SHUTDOWN_GRACE_SECS")]
+    shutdown_grace_secs: u64,
+
+    /// Port to serve the gRPC API on, next to the web service (not served if not given)
+    #[cfg(feature = "grpc")]
+    #[arg(long, env = "LINT_GRPC_PORT")]
+    grpc_port: Option<u16>,
+
+    /// Number of lint results the web service keeps in memory for repeated inputs (0 to disable)
+    #[cfg(feature = "service")]
+    #[arg(long, default_value_t = 1024, env = "LINT_CACHE_ENTRIES")]
+    cache_entries: usize,
+
+    /// Origin allowed to call the web service from a browser, e.g. https://example.com;
+    /// repeat for several origins, or give * to allow any
+    #[cfg(feature = "service")]
+    #[arg(long = "cors-origin", env = "LINT_CORS_ORIGINS", value_delimiter = ',')]
+    cors_origins: Vec<String>,
+
+    /// OTLP/gRPC endpoint of an OpenTelemetry collector to send the web service's traces to,
+    /// e.g. http://localhost:4317
+    #[cfg(feature = "otel")]
+    #[arg(long, env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
+    otlp_endpoint: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Lint whole source trees
+    #[command(subcommand)]
+    Corpus(CorpusCommand),
+}
+
+#[derive(Subcommand, Debug)]
+enum CorpusCommand {
+    /// Lint every supported file under a directory, such as a checkout of a known-good
+    /// repository, and fail if any does not meet the expectation
+    Run(corpus::RunArgs),
+}
+
+/// The format of log lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogFormat {
+    /// Human-readable lines, coloured when written to a terminal.
+    Text,
+    /// One JSON object per line, for log collectors.
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("unknown log format '{}' (expected text or json)", s)),
+        }
+    }
+}
+
+/// Builds the layer writing log lines, filtered by `RUST_LOG`, to `file` or else to stderr.
+/// Logs never go to stdout, which carries only results.
+fn log_layer(format: LogFormat, file: Option<&std::path::Path>) -> std::io::Result<Box<dyn Layer<Registry> + Send + Sync>> {
+    let layer = tracing_subscriber::fmt::layer();
+    let layer: Box<dyn Layer<Registry> + Send + Sync> = match (format, file) {
+        (LogFormat::Text, None) => Box::new(layer.with_writer(std::io::stderr).with_ansi(std::io::stderr().is_terminal())),
+        (LogFormat::Json, None) => Box::new(layer.json().with_writer(std::io::stderr)),
+        (format, Some(path)) => {
+            let file = std::sync::Mutex::new(fs::OpenOptions::new().create(true).append(true).open(path)?);
+            match format {
+                LogFormat::Text => Box::new(layer.with_writer(file).with_ansi(false)),
+                LogFormat::Json => Box::new(layer.json().with_writer(file)),
+            }
+        }
+    };
+    Ok(Box::new(layer.with_filter(EnvFilter::from_default_env())))
+}
+
+/// Lints `code`, reusing the result stored in `dir` for identical input if there is one.
+///
+/// Problems with the cache itself are logged and otherwise ignored.
+fn lint_with_disk_cache(dir: &std::path::Path, language: &str, code: &str, options: &LintOptions) -> Result<LintReport, LinterError> {
+    let cache = match DiskCache::open(dir) {
+        Ok(cache) => cache,
+        Err(e) => {
+            warn!("Cannot use cache directory {}: {}", dir.display(), e);
+            return lintymclintface::lint_with_options(language, code, options);
+        }
+    };
+    let key = CacheKey::new(language, code, options);
+    if let Some(report) = cache.get(&key) {
+        debug!("Using cached result {}", key.as_str());
+        return Ok(report);
+    }
+    let report = lintymclintface::lint_with_options(language, code, options)?;
+    if let Err(e) = cache.put(&key, &report) {
+        warn!("Failed to write to cache directory {}: {}", dir.display(), e);
+    }
+    Ok(report)
 }
 
 /// Main entry point for the lintymclintface application.
 ///
 /// It can run as a command-line tool for linting a single file,
 /// or as a web service for handling linting requests via HTTP.
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    info!("Application started.");
+fn main() -> std::io::Result<()> {
+    let cli = Cli::parse();
+
     // Initialize tracing subscriber
-    let subscriber = FmtSubscriber::builder()
-        .with_env_filter(EnvFilter::from_default_env())
-        .with_writer(std::io::stdout) // Direct logs to stdout
-        .with_ansi(std::io::stdout().is_terminal()) // Enable ANSI only if stdout is a TTY
-        .finish();
+    let logs = log_layer(cli.log_format, cli.log_file.as_deref())
+        .map_err(|e| std::io::Error::new(e.kind(), format!("Cannot open log file: {}", e)))?;
+    // Traces are exported whatever RUST_LOG says, as they only cover the spans at info level.
+    #[cfg(feature = "otel")]
+    let (traces, _telemetry) = match cli.otlp_endpoint.as_deref().filter(|_| cli.service) {
+        Some(endpoint) => {
+            let (layer, telemetry) = service::telemetry::layer(endpoint)?;
+            (Some(layer.with_filter(tracing_subscriber::filter::LevelFilter::INFO)), Some(telemetry))
+        }
+        None => (None, None),
+    };
+    #[cfg(not(feature = "otel"))]
+    let traces: Option<tracing_subscriber::layer::Identity> = None;
+    let subscriber = tracing_subscriber::registry().with(logs).with(traces);
     tracing::subscriber::set_global_default(subscriber)
         .expect("setting default subscriber failed");
-
-    register_metrics(); // Register Prometheus metrics
-
-    let cli = Cli::parse();
+    info!("Application started.");
 
     // If no arguments are provided, print help and exit
     if std::env::args().len() == 1 {
@@ -150,37 +275,84 @@ async fn main() -> std::io::Result<()> {
         return Ok(());
     }
 
+    let options = LintOptions {
+        timeout: (cli.parse_timeout_ms > 0).then(|| std::time::Duration::from_millis(cli.parse_timeout_ms)),
+        max_diagnostics: cli.max_diagnostics,
+        ..LintOptions::default()
+    };
+
+    if let Some(Command::Corpus(CorpusCommand::Run(args))) = &cli.command {
+        let passed = corpus::run(args, &options, cli.cache_dir.as_deref(), cli.max_input_bytes, cli.metrics_file.as_deref())?;
+        if !passed {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    #[cfg(feature = "service")]
     if cli.service {
-        info!("Starting lintymclintface in web service mode on 0.0.0.0:{}", cli.port);
-        HttpServer::new(|| {
-            App::new()
-                .route("/lint", web::post().to(lint_service))
-                .route("/metrics", web::get().to(metrics)) // Add metrics endpoint
-        })
-        .bind(format!("0.0.0.0:{}", cli.port))?
-        .run()
-        .await
-    } else if let (Some(language), Some(file_path)) = (cli.language, cli.file) {
+        return service::run(service::Config {
+            bind: cli.bind,
+            port: cli.port,
+            unix_socket: cli.unix_socket,
+            #[cfg(feature = "tls")]
+            tls_cert: cli.tls_cert,
+            #[cfg(feature = "tls")]
+            tls_key: cli.tls_key,
+            max_batch_size: cli.max_batch_size,
+            max_archive_bytes: cli.max_archive_bytes,
+            max_archive_files: cli.max_archive_files,
+            job_ttl: std::time::Duration::from_secs(cli.job_ttl_secs),
+            max_jobs: cli.max_jobs,
+            max_input_bytes: cli.max_input_bytes,
+            max_bulk_bytes: cli.max_bulk_bytes,
+            lint_options: options,
+            api_keys_file: cli.api_keys_file,
+            rate_limit: cli.rate_limit,
+            rate_limit_burst: cli.rate_limit_burst,
+            shutdown_grace: std::time::Duration::from_secs(cli.shutdown_grace_secs),
+            #[cfg(feature = "grpc")]
+            grpc_port: cli.grpc_port,
+            cache_entries: cli.cache_entries,
+            cors_origins: cli.cors_origins,
+        });
+    }
+
+    if let (Some(language), Some(file_path)) = (cli.language, cli.file) {
+        let mut metrics = cli.metrics_file.as_ref().map(|_| textfile::RunMetrics::new());
         info!("Linting {} file: {}", language, file_path);
-        let code = fs::read_to_string(&file_path)
-            .map_err(|e| LinterError::Io(format!("Failed to read file: {}", e)))?;
-
-        let result = match language.as_str() {
-            "java" => linters::java::lint(&code),
-            "python" => linters::python::lint(&code),
-            "r" => linters::r::lint(&code),
-            _ => Err(LinterError::UnsupportedLanguage(language.clone())),
+        let size = fs::metadata(&file_path)
+            .map_err(|e| LinterError::Io(format!("Failed to read file: {}", e)))?
+            .len();
+
+        let result = if size > cli.max_input_bytes as u64 {
+            Err(LinterError::Io(format!("File is {} bytes, exceeding the limit of {}", size, cli.max_input_bytes)))
+        } else {
+            let code = fs::read_to_string(&file_path)
+                .map_err(|e| LinterError::Io(format!("Failed to read file: {}", e)))?;
+            match &cli.cache_dir {
+                Some(dir) => lint_with_disk_cache(dir, &language, &code, &options),
+                None => lintymclintface::lint_with_options(&language, &code, &options),
+            }
         };
 
+        if let (Some(metrics), Some(path)) = (&mut metrics, &cli.metrics_file) {
+            metrics.record(&language, &result);
+            metrics.write(path).map_err(|e| std::io::Error::new(e.kind(), format!("Cannot write metrics file {}: {}", path.display(), e)))?;
+        }
+
         match result {
-            Ok(errors) => {
-                if errors.is_empty() {
+            Ok(report) => {
+                if report.errors.is_empty() {
                     info!("No syntax errors found.");
-                } else {
-                    // Serialize errors to JSON and print to stdout
-                    let json_errors = serde_json::to_string_pretty(&errors)
-                        .map_err(|e| LinterError::Io(format!("Failed to serialize errors to JSON: {}", e)))?;
-                    println!("{}", json_errors);
+                }
+                if report.truncated {
+                    warn!("Only the first {} diagnostics are reported.", report.errors.len());
+                }
+                let rendered = output::render(cli.format, &file_path, &report)
+                    .map_err(|e| LinterError::Io(format!("Failed to render lint results: {}", e)))?;
+                if !rendered.is_empty() {
+                    println!("{}", rendered);
                 }
             },
             Err(e) => {
@@ -191,10 +363,18 @@ async fn main() -> std::io::Result<()> {
                     LinterError::Parse(msg) => format!("Parse Error: {}", msg),
                     LinterError::TreeSitterParseError(msg) => format!("Tree-sitter Parse Error: {}", msg),
                     LinterError::UnsupportedLanguage(msg) => format!("Unsupported Language Error: {}", msg),
+                    LinterError::Timeout(msg) => format!("Timeout Error: {}", msg),
+                    LinterError::InvalidOption(msg) => format!("Invalid Option Error: {}", msg),
                 };
-                let structured_error = serde_json::to_string_pretty(&vec![SyntaxError { line: 0, column: 0, message: error_msg }])
-                    .map_err(|e| LinterError::Io(format!("Failed to serialize error to JSON: {}", e)))?;
-                eprintln!("{}", structured_error);
+                match cli.format {
+                    OutputFormat::Json => {
+                        let structured_error = serde_json::to_string_pretty(&vec![SyntaxError { line: 0, column: 0, message: error_msg, severity: Severity::Error, rule: "linter-error".to_string() }])
+                            .map_err(|e| LinterError::Io(format!("Failed to serialize error to JSON: {}", e)))?;
+                        eprintln!("{}", structured_error);
+                    },
+                    OutputFormat::Unix => eprintln!("{}: error: {}", file_path, error_msg),
+                    OutputFormat::Ndjson => eprintln!("{}", serde_json::json!({ "path": file_path, "error": error_msg })),
+                }
                 std::process::exit(1);
             }
         }
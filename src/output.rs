@@ -0,0 +1,83 @@
+//! Rendering of lint results for the command-line interface.
+
+use crate::{LintReport, SyntaxError};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// The format used to print lint results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[cfg_attr(feature = "service", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// A pretty-printed JSON array of errors.
+    #[default]
+    Json,
+    /// Compiler-style `path:line:col: severity: message [rule]` lines, one per error.
+    ///
+    /// This is understood by Vim's quickfix list, Emacs `compile` mode and other
+    /// `grep`-style tooling.
+    Unix,
+    /// One `{"path": ..., "errors": [...]}` JSON object per line, one line per file.
+    ///
+    /// Unlike the other formats, a line is printed for clean files too, so that consumers
+    /// can follow progress as each file finishes.
+    Ndjson,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(OutputFormat::Json),
+            "unix" => Ok(OutputFormat::Unix),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            _ => Err(format!("unknown output format '{}' (expected json, unix or ndjson)", s)),
+        }
+    }
+}
+
+/// One line of [`OutputFormat::Ndjson`] output.
+#[derive(Serialize)]
+struct FileRecord<'a> {
+    path: &'a str,
+    errors: &'a [SyntaxError],
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    truncated: bool,
+}
+
+/// Renders the errors found in `path` in the given format.
+///
+/// Returns an empty string when there are no errors, so that clean files produce no output,
+/// except in [`OutputFormat::Ndjson`] which always reports the file and whether its errors
+/// were truncated.
+pub fn render(format: OutputFormat, path: &str, report: &LintReport) -> Result<String, serde_json::Error> {
+    let errors = &report.errors;
+    if errors.is_empty() && format != OutputFormat::Ndjson {
+        return Ok(String::new());
+    }
+    match format {
+        OutputFormat::Ndjson => serde_json::to_string(&FileRecord { path, errors, truncated: report.truncated }),
+        OutputFormat::Json => serde_json::to_string_pretty(errors),
+        OutputFormat::Unix => Ok(errors
+            .iter()
+            .map(|e| format_unix_line(path, e))
+            .collect::<Vec<_>>()
+            .join("\n")),
+    }
+}
+
+/// Formats a single error as `path:line:col: severity: message [rule]`.
+///
+/// Newlines in the message are escaped so that every error stays on one line.
+pub fn format_unix_line(path: &str, error: &SyntaxError) -> String {
+    format!(
+        "{}:{}:{}: {}: {} [{}]",
+        path,
+        error.line,
+        error.column,
+        error.severity,
+        error.message.replace('\n', "\\n"),
+        error.rule
+    )
+}
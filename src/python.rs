@@ -0,0 +1,108 @@
+//! Python bindings, built as a native extension module with `maturin` (see `pyproject.toml`).
+//!
+//! The linters run in-process; the GIL is released while the code is parsed.
+
+use crate::{LintOptions, LinterError, SyntaxError};
+use pyo3::exceptions::{PyOSError, PyRuntimeError, PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::Path;
+use std::time::Duration;
+
+/// A single diagnostic reported by the linter.
+#[pyclass(name = "Diagnostic", module = "lintymclintface", frozen, get_all)]
+#[derive(Clone)]
+struct PyDiagnostic {
+    line: usize,
+    column: usize,
+    message: String,
+    severity: String,
+    rule: String,
+}
+
+#[pymethods]
+impl PyDiagnostic {
+    /// Returns the diagnostic as a plain dict, matching the CLI's JSON output.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("line", self.line)?;
+        dict.set_item("column", self.column)?;
+        dict.set_item("message", &self.message)?;
+        dict.set_item("severity", &self.severity)?;
+        dict.set_item("rule", &self.rule)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Diagnostic(line={}, column={}, severity='{}', rule='{}', message={:?})",
+            self.line, self.column, self.severity, self.rule, self.message
+        )
+    }
+}
+
+impl From<SyntaxError> for PyDiagnostic {
+    fn from(error: SyntaxError) -> Self {
+        PyDiagnostic {
+            line: error.line,
+            column: error.column,
+            message: error.message,
+            severity: error.severity.to_string(),
+            rule: error.rule,
+        }
+    }
+}
+
+fn to_py_err(err: LinterError) -> PyErr {
+    match err {
+        LinterError::Io(msg) => PyOSError::new_err(msg),
+        LinterError::UnsupportedLanguage(msg) => PyValueError::new_err(format!("Unsupported language: {}", msg)),
+        LinterError::InvalidOption(msg) => PyValueError::new_err(msg),
+        LinterError::Parse(msg) | LinterError::TreeSitterParseError(msg) => PyRuntimeError::new_err(msg),
+        LinterError::Timeout(msg) => PyTimeoutError::new_err(msg),
+    }
+}
+
+fn into_diagnostics(result: Result<Vec<SyntaxError>, LinterError>) -> PyResult<Vec<PyDiagnostic>> {
+    result.map(|errors| errors.into_iter().map(PyDiagnostic::from).collect()).map_err(to_py_err)
+}
+
+/// Options abandoning the parse after `timeout_ms` milliseconds, if given.
+fn options(timeout_ms: Option<u64>) -> LintOptions {
+    LintOptions { timeout: timeout_ms.map(Duration::from_millis), ..LintOptions::default() }
+}
+
+/// Lints `code` written in `language` (java, python or r) and returns a list of diagnostics.
+/// Raises `TimeoutError` if parsing takes longer than `timeout_ms`.
+#[pyfunction]
+#[pyo3(signature = (code, language, timeout_ms=None))]
+fn lint(py: Python<'_>, code: &str, language: &str, timeout_ms: Option<u64>) -> PyResult<Vec<PyDiagnostic>> {
+    into_diagnostics(py.allow_threads(|| crate::lint_with_options(language, code, &options(timeout_ms)).map(|report| report.errors)))
+}
+
+/// Lints the file at `path`. The language is detected from the extension unless given.
+#[pyfunction]
+#[pyo3(signature = (path, language=None, timeout_ms=None))]
+fn lint_file(py: Python<'_>, path: &str, language: Option<&str>, timeout_ms: Option<u64>) -> PyResult<Vec<PyDiagnostic>> {
+    let language = match language {
+        Some(language) => language.to_string(),
+        None => crate::detect_language(Path::new(path))
+            .ok_or_else(|| PyValueError::new_err(format!("Cannot detect the language of '{}'", path)))?
+            .to_string(),
+    };
+    into_diagnostics(py.allow_threads(|| {
+        let code = std::fs::read_to_string(path)
+            .map_err(|e| LinterError::Io(format!("Failed to read file: {}", e)))?;
+        crate::lint_with_options(&language, &code, &options(timeout_ms)).map(|report| report.errors)
+    }))
+}
+
+#[pymodule]
+fn lintymclintface(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyDiagnostic>()?;
+    m.add_function(wrap_pyfunction!(lint, m)?)?;
+    m.add_function(wrap_pyfunction!(lint_file, m)?)?;
+    m.add("SUPPORTED_LANGUAGES", crate::SUPPORTED_LANGUAGES.to_vec())?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
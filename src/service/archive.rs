@@ -0,0 +1,172 @@
+//! In-memory extraction of project archives uploaded to `POST /lint/archive`.
+
+use flate2::read::GzDecoder;
+use std::collections::HashSet;
+use std::io::{Cursor, Read};
+use std::path::{Component, Path};
+
+/// Limits applied while unpacking an archive.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Maximum total size of all files once decompressed.
+    pub max_bytes: u64,
+    /// Maximum number of files in the archive.
+    pub max_files: usize,
+}
+
+/// A file unpacked from an archive.
+pub struct Entry {
+    pub path: String,
+    pub contents: Vec<u8>,
+}
+
+/// An entry that was not extracted, with the reason why.
+pub struct Skipped {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Why an archive was rejected as a whole.
+#[derive(Debug)]
+pub enum ArchiveError {
+    /// The body is neither a gzip-compressed tarball nor a zip file.
+    UnknownFormat,
+    /// The archive is corrupt or could not be read.
+    Invalid(String),
+    /// The archive exceeds one of the configured [`Limits`].
+    TooLarge(String),
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveError::UnknownFormat => write!(f, "Unsupported archive format; expected .tar.gz or .zip"),
+            ArchiveError::Invalid(msg) => write!(f, "Invalid archive: {}", msg),
+            ArchiveError::TooLarge(msg) => write!(f, "Archive too large: {}", msg),
+        }
+    }
+}
+
+/// Unpacks a `.tar.gz` or `.zip` archive, detected from its magic bytes.
+///
+/// Only regular files are extracted. Links, directories, entries whose path is absolute
+/// or escapes the archive root, and later entries whose path normalizes to that of an
+/// earlier file are reported as skipped.
+pub fn extract(data: &[u8], limits: Limits) -> Result<(Vec<Entry>, Vec<Skipped>), ArchiveError> {
+    let mut extractor = Extractor { limits, total_bytes: 0, entries: Vec::new(), paths: HashSet::new(), skipped: Vec::new() };
+    if data.starts_with(&[0x1f, 0x8b]) {
+        extractor.extract_tar_gz(data)?;
+    } else if data.starts_with(b"PK\x03\x04") || data.starts_with(b"PK\x05\x06") {
+        extractor.extract_zip(data)?;
+    } else {
+        return Err(ArchiveError::UnknownFormat);
+    }
+    Ok((extractor.entries, extractor.skipped))
+}
+
+struct Extractor {
+    limits: Limits,
+    total_bytes: u64,
+    entries: Vec<Entry>,
+    /// The normalized paths of `entries`.
+    paths: HashSet<String>,
+    skipped: Vec<Skipped>,
+}
+
+impl Extractor {
+    fn extract_tar_gz(&mut self, data: &[u8]) -> Result<(), ArchiveError> {
+        let mut archive = tar::Archive::new(GzDecoder::new(data));
+        let entries = archive.entries().map_err(|e| ArchiveError::Invalid(e.to_string()))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| ArchiveError::Invalid(e.to_string()))?;
+            let raw_path = String::from_utf8_lossy(&entry.path_bytes()).to_string();
+            let entry_type = entry.header().entry_type();
+            if entry_type.is_dir() || entry_type.is_gnu_longname() || entry_type.is_pax_global_extensions() {
+                continue;
+            }
+            self.check_file_count()?;
+            if !entry_type.is_file() {
+                self.skip(raw_path, "not a regular file");
+                continue;
+            }
+            self.add(raw_path, entry)?;
+        }
+        Ok(())
+    }
+
+    fn extract_zip(&mut self, data: &[u8]) -> Result<(), ArchiveError> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(data)).map_err(|e| ArchiveError::Invalid(e.to_string()))?;
+        for i in 0..archive.len() {
+            let file = archive.by_index(i).map_err(|e| ArchiveError::Invalid(e.to_string()))?;
+            let raw_path = file.name().to_string();
+            if file.is_dir() {
+                continue;
+            }
+            self.check_file_count()?;
+            if file.is_symlink() {
+                self.skip(raw_path, "not a regular file");
+                continue;
+            }
+            self.add(raw_path, file)?;
+        }
+        Ok(())
+    }
+
+    /// Reads one file, enforcing the limits on the decompressed data rather than on sizes
+    /// declared in the archive headers, which can lie.
+    fn add(&mut self, raw_path: String, reader: impl Read) -> Result<(), ArchiveError> {
+        let Some(path) = sanitize_path(&raw_path) else {
+            self.skip(raw_path, "path is absolute or escapes the archive root");
+            return Ok(());
+        };
+        if self.paths.contains(&path) {
+            self.skip(raw_path, &format!("duplicate of an earlier entry for {}", path));
+            return Ok(());
+        }
+        let remaining = self.limits.max_bytes - self.total_bytes;
+        let mut contents = Vec::new();
+        reader
+            .take(remaining + 1)
+            .read_to_end(&mut contents)
+            .map_err(|e| ArchiveError::Invalid(format!("{}: {}", path, e)))?;
+        if contents.len() as u64 > remaining {
+            return Err(ArchiveError::TooLarge(format!("more than {} bytes once decompressed", self.limits.max_bytes)));
+        }
+        self.total_bytes += contents.len() as u64;
+        self.paths.insert(path.clone());
+        self.entries.push(Entry { path, contents });
+        Ok(())
+    }
+
+    /// Counts skipped entries too, so that an archive of millions of links is rejected early.
+    fn check_file_count(&self) -> Result<(), ArchiveError> {
+        if self.entries.len() + self.skipped.len() >= self.limits.max_files {
+            return Err(ArchiveError::TooLarge(format!("more than {} files", self.limits.max_files)));
+        }
+        Ok(())
+    }
+
+    fn skip(&mut self, path: String, reason: &str) {
+        self.skipped.push(Skipped { path, reason: reason.to_string() });
+    }
+}
+
+/// Normalizes an archive entry path to a relative `a/b/c` form.
+///
+/// Returns `None` for absolute paths, Windows drive prefixes and paths containing `..`,
+/// so that a crafted archive can never refer to anything outside its own root.
+fn sanitize_path(raw: &str) -> Option<String> {
+    let raw = raw.replace('\\', "/");
+    let mut parts = Vec::new();
+    for component in Path::new(&raw).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    if parts.is_empty() || parts[0].contains(':') {
+        return None;
+    }
+    Some(parts.join("/"))
+}
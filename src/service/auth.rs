@@ -0,0 +1,192 @@
+//! API-key authentication and per-client rate limiting for the HTTP service.
+//!
+//! When started with `--api-keys-file`, every request except those to [`PUBLIC_PATHS`] must
+//! present a key from that file, either as `Authorization: Bearer <key>` or as `X-API-Key: <key>`.
+//! The file has one `<client> <key>` pair per line; blank lines and lines starting with `#`
+//! are ignored. The client name is used in logs and metrics, so the key itself never is.
+//!
+//! With `--rate-limit`, each client gets a token bucket refilled at that many requests per
+//! second and holding up to `--rate-limit-burst` requests. Clients are told when to retry
+//! with `429 Too Many Requests` and a `Retry-After` header. Without API keys, clients are
+//! told apart by their IP address.
+//!
+//! The gRPC API applies the same checks through [`Access::admit`], reading the key from the
+//! `authorization` or `x-api-key` metadata.
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header;
+use actix_web::middleware::Next;
+use actix_web::{web, HttpRequest, HttpResponse};
+use lru::LruCache;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use super::openapi::ApiError;
+use super::{LINT_CLIENT_REQUESTS_TOTAL, LINT_RATE_LIMITED_TOTAL};
+
+/// Paths served without authentication or rate limiting.
+const PUBLIC_PATHS: &[&str] = &["/", "/metrics", "/healthz", "/readyz", "/version", "/openapi.json"];
+
+/// Header accepted as an alternative to `Authorization: Bearer`.
+const API_KEY_HEADER: &str = "X-API-Key";
+
+/// Metrics label for requests from clients without an API key.
+const ANONYMOUS: &str = "anonymous";
+
+/// Most clients the rate limiter keeps a bucket for; the least recently seen client's bucket
+/// goes first, so memory and the cost of a request stay bounded however many clients call.
+const MAX_BUCKETS: NonZeroUsize = NonZeroUsize::new(10_000).unwrap();
+
+/// The API keys loaded from `--api-keys-file`, mapped to client names.
+pub struct ApiKeys {
+    clients: HashMap<String, String>,
+}
+
+impl ApiKeys {
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents).map_err(|msg| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", path.display(), msg))
+        })
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut clients = HashMap::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let (Some(client), Some(key), None) = (fields.next(), fields.next(), fields.next()) else {
+                return Err(format!("line {}: expected \"<client> <key>\"", number + 1));
+            };
+            if clients.insert(key.to_string(), client.to_string()).is_some() {
+                return Err(format!("line {}: duplicate key", number + 1));
+            }
+        }
+        Ok(ApiKeys { clients })
+    }
+
+    fn client(&self, key: &str) -> Option<&str> {
+        self.clients.get(key).map(String::as_str)
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// A token bucket per client.
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    buckets: Mutex<LruCache<String, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Allows `rate` requests per second per client, with bursts of up to `burst` requests.
+    pub fn new(rate: f64, burst: u32) -> Self {
+        RateLimiter { rate, burst: f64::from(burst.max(1)), buckets: Mutex::new(LruCache::new(MAX_BUCKETS)) }
+    }
+
+    /// Takes a token from `client`'s bucket, or returns how long until one is available.
+    fn acquire(&self, client: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.get_or_insert_mut(client.to_string(), || Bucket { tokens: self.burst, updated: now });
+        bucket.tokens = (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * self.rate).min(self.burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+        }
+    }
+}
+
+/// Why [`Access::admit`] refused a request.
+pub enum Rejection {
+    /// No key, or an unknown one, was presented.
+    Unauthorized,
+    /// The client is over its rate limit and may retry after this many seconds.
+    RateLimited(u64),
+}
+
+/// The authentication and rate-limiting settings shared by all workers, and by the gRPC API.
+#[derive(Default)]
+pub struct Access {
+    pub keys: Option<ApiKeys>,
+    pub limiter: Option<RateLimiter>,
+}
+
+impl Access {
+    /// Authenticates a request that presented `key` from `peer`, counts it in the per-client
+    /// metrics and takes a token from the client's rate-limiter bucket.
+    pub fn admit(&self, key: Option<&str>, peer: Option<IpAddr>) -> Result<(), Rejection> {
+        let client = match &self.keys {
+            Some(keys) => Some(key.and_then(|key| keys.client(key)).ok_or(Rejection::Unauthorized)?),
+            None => None,
+        };
+        let label = client.unwrap_or(ANONYMOUS);
+        LINT_CLIENT_REQUESTS_TOTAL.with_label_values(&[label]).inc();
+
+        if let Some(limiter) = &self.limiter {
+            let bucket = match client {
+                Some(client) => client.to_string(),
+                None => peer.map(|ip| ip.to_string()).unwrap_or_default(),
+            };
+            if let Err(wait) = limiter.acquire(&bucket) {
+                LINT_RATE_LIMITED_TOTAL.with_label_values(&[label]).inc();
+                return Err(Rejection::RateLimited(wait.as_secs_f64().ceil().max(1.0) as u64));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The key presented with `req`, if any.
+fn presented_key(req: &HttpRequest) -> Option<&str> {
+    let headers = req.headers();
+    if let Some(bearer) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        return Some(bearer.trim());
+    }
+    headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()).map(str::trim)
+}
+
+/// Middleware that authenticates and rate-limits every request outside [`PUBLIC_PATHS`].
+pub async fn guard(req: ServiceRequest, next: Next<impl MessageBody + 'static>) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let access = req.app_data::<web::Data<Access>>().cloned().unwrap_or_default();
+    if PUBLIC_PATHS.contains(&req.path()) {
+        return next.call(req).await.map(ServiceResponse::map_into_boxed_body);
+    }
+
+    match access.admit(presented_key(req.request()), req.peer_addr().map(|addr| addr.ip())) {
+        Ok(()) => next.call(req).await.map(ServiceResponse::map_into_boxed_body),
+        Err(Rejection::Unauthorized) => {
+            warn!("Rejected request to {} without a valid API key", req.path());
+            let response = HttpResponse::Unauthorized()
+                .insert_header((header::WWW_AUTHENTICATE, "Bearer"))
+                .json(ApiError { error: "A valid API key is required".to_string() });
+            Ok(req.into_response(response))
+        }
+        Err(Rejection::RateLimited(retry_after)) => {
+            let response = HttpResponse::TooManyRequests()
+                .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+                .json(ApiError { error: format!("Rate limit exceeded; retry after {} s", retry_after) });
+            Ok(req.into_response(response))
+        }
+    }
+}
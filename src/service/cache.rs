@@ -0,0 +1,36 @@
+//! The in-memory cache of recent lint results shared by all service endpoints.
+
+use lintymclintface::cache::CacheKey;
+use lintymclintface::{LintOptions, LintReport, LintTimings, LinterError};
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, OnceLock};
+
+use super::{LINT_CACHE_HITS_TOTAL, LINT_CACHE_MISSES_TOTAL};
+
+/// The most recently used results, or `None` when caching is disabled.
+static RESULTS: OnceLock<Option<Mutex<LruCache<CacheKey, LintReport>>>> = OnceLock::new();
+
+/// Keeps up to `capacity` results; 0 disables caching. Only the first call has an effect.
+pub fn init(capacity: usize) {
+    RESULTS.get_or_init(|| NonZeroUsize::new(capacity).map(|capacity| Mutex::new(LruCache::new(capacity))));
+}
+
+/// Lints `code`, answering from the cache when the same input was linted before.
+/// Errors, such as timeouts, are not cached.
+///
+/// Returns the time spent in each phase of linting, or `None` for a cached result.
+pub fn lint(language: &str, code: &str, options: &LintOptions) -> Result<(LintReport, Option<LintTimings>), LinterError> {
+    let Some(Some(results)) = RESULTS.get() else {
+        return lintymclintface::lint_timed(language, code, options).map(|(report, timings)| (report, Some(timings)));
+    };
+    let key = CacheKey::new(language, code, options);
+    if let Some(report) = results.lock().unwrap().get(&key) {
+        LINT_CACHE_HITS_TOTAL.inc();
+        return Ok((report.clone(), None));
+    }
+    LINT_CACHE_MISSES_TOTAL.inc();
+    let (report, timings) = lintymclintface::lint_timed(language, code, options)?;
+    results.lock().unwrap().put(key, report.clone());
+    Ok((report, Some(timings)))
+}
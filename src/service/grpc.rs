@@ -0,0 +1,340 @@
+//! The gRPC API, served with `--grpc-port` next to the HTTP service.
+//!
+//! The service is generated from `proto/lint.proto` and mirrors the REST API: `Lint` and
+//! `LintBatch` go through the same linters, limits and metrics as `POST /v1/lint` and
+//! `POST /v1/lint/batch`, and `LintStream` keeps a live-lint [`Session`] like `/v1/ws`.
+//! API keys and rate limits apply as for HTTP, with the key sent as `authorization: Bearer`
+//! or `x-api-key` metadata. With `--tls-cert`, the gRPC API is served over TLS too. Calls are
+//! logged under the request ID in their `x-request-id` metadata, or a generated one.
+
+use futures_util::Stream;
+use lintymclintface::document::{Position as DocumentPosition, TextEdit as DocumentTextEdit};
+use lintymclintface::{LintReport, LinterError, Severity as LintSeverity, SyntaxError};
+use rayon::prelude::*;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{info, Instrument};
+
+use super::auth::{Access, Rejection};
+use super::live::{ClientMessage, Session};
+use super::request_id::{self, REQUEST_ID_HEADER};
+use super::{in_current_span, lint_and_record, lint_batch_item, linter_error_message, BatchItem, BatchItemResult, Config};
+use super::LintRequest as RestLintRequest;
+
+mod proto {
+    tonic::include_proto!("lintymclintface.v1");
+}
+
+use proto::linter_server::{Linter, LinterServer};
+use proto::{
+    lint_stream_request, lint_stream_response, BatchFile, Diagnostic, FileResult, LintBatchRequest, LintBatchResponse,
+    LintRequest, LintResponse, LintStreamRequest, LintStreamResponse, Severity,
+};
+
+impl From<SyntaxError> for Diagnostic {
+    fn from(error: SyntaxError) -> Self {
+        let severity = match error.severity {
+            LintSeverity::Warning => Severity::Warning,
+            LintSeverity::Error => Severity::Error,
+        };
+        Diagnostic {
+            line: error.line as u32,
+            column: error.column as u32,
+            message: error.message,
+            severity: severity.into(),
+            rule: error.rule,
+        }
+    }
+}
+
+impl From<LintReport> for LintResponse {
+    fn from(report: LintReport) -> Self {
+        LintResponse { diagnostics: report.errors.into_iter().map(Diagnostic::from).collect(), truncated: report.truncated }
+    }
+}
+
+impl From<LintRequest> for RestLintRequest {
+    fn from(request: LintRequest) -> Self {
+        let min_severity = match request.min_severity() {
+            Severity::Unspecified => None,
+            Severity::Warning => Some(LintSeverity::Warning),
+            Severity::Error => Some(LintSeverity::Error),
+        };
+        RestLintRequest {
+            language: Some(request.language).filter(|language| !language.is_empty()),
+            code: request.code,
+            filename: request.filename,
+            rules: Some(request.rules).filter(|rules| !rules.is_empty()),
+            disable: request.disable,
+            min_severity,
+            target_version: request.target_version,
+            format: None,
+        }
+    }
+}
+
+impl From<BatchFile> for BatchItem {
+    fn from(file: BatchFile) -> Self {
+        BatchItem { path: file.path, language: file.language, code: file.code }
+    }
+}
+
+impl From<BatchItemResult> for FileResult {
+    fn from(result: BatchItemResult) -> Self {
+        FileResult {
+            path: result.path,
+            language: result.language,
+            diagnostics: result.errors.into_iter().map(Diagnostic::from).collect(),
+            truncated: result.truncated,
+            error: result.error,
+        }
+    }
+}
+
+fn to_status(e: LinterError) -> Status {
+    let code = match &e {
+        LinterError::UnsupportedLanguage(_) | LinterError::Parse(_) | LinterError::InvalidOption(_) => tonic::Code::InvalidArgument,
+        LinterError::Timeout(_) => tonic::Code::DeadlineExceeded,
+        LinterError::Io(_) | LinterError::TreeSitterParseError(_) => tonic::Code::Internal,
+    };
+    Status::new(code, linter_error_message(e))
+}
+
+/// Converts a streamed request into the equivalent live-lint message.
+fn client_message(request: LintStreamRequest) -> Result<ClientMessage, String> {
+    let position = |p: Option<proto::Position>| {
+        p.map(|p| DocumentPosition { line: p.line as usize, column: p.column as usize })
+            .ok_or_else(|| "Edits need a start and an end position".to_string())
+    };
+    match request.message {
+        Some(lint_stream_request::Message::Open(open)) => Ok(ClientMessage::Open { language: open.language, text: open.text }),
+        Some(lint_stream_request::Message::Edit(edit)) => {
+            let edits = edit
+                .edits
+                .into_iter()
+                .map(|e| Ok(DocumentTextEdit { start: position(e.start)?, end: position(e.end)?, text: e.text }))
+                .collect::<Result<_, String>>()?;
+            Ok(ClientMessage::Edit { edits })
+        }
+        None => Err("Empty message; send \"open\" or \"edit\"".to_string()),
+    }
+}
+
+struct LinterService {
+    config: Arc<Config>,
+    access: Arc<Access>,
+}
+
+impl LinterService {
+    /// Applies the same API-key and rate-limit checks as the HTTP middleware.
+    #[allow(clippy::result_large_err)] // `Status` is what every handler returns anyway.
+    fn admit<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        let metadata = request.metadata();
+        let key = metadata
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .or_else(|| metadata.get("x-api-key").and_then(|v| v.to_str().ok()))
+            .map(str::trim);
+        match self.access.admit(key, request.remote_addr().map(|addr| addr.ip())) {
+            Ok(()) => Ok(()),
+            Err(Rejection::Unauthorized) => Err(Status::unauthenticated("A valid API key is required")),
+            Err(Rejection::RateLimited(retry_after)) => {
+                let mut status = Status::resource_exhausted(format!("Rate limit exceeded; retry after {} s", retry_after));
+                if let Ok(value) = retry_after.to_string().parse() {
+                    status.metadata_mut().insert("retry-after", value);
+                }
+                Err(status)
+            }
+        }
+    }
+}
+
+type LintStreamResult = Pin<Box<dyn Stream<Item = Result<LintStreamResponse, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl Linter for LinterService {
+    async fn lint(&self, request: Request<LintRequest>) -> Result<Response<LintResponse>, Status> {
+        self.admit(&request)?;
+        let request = RestLintRequest::from(request.into_inner());
+        if request.code.len() > self.config.max_input_bytes {
+            return Err(Status::resource_exhausted(format!(
+                "Code of {} bytes exceeds the limit of {}",
+                request.code.len(),
+                self.config.max_input_bytes
+            )));
+        }
+        let config = self.config.clone();
+        let result = tokio::task::spawn_blocking(in_current_span(move || {
+            let language = request.language()?;
+            info!("Received gRPC lint request for language: {}", language);
+            lint_and_record(language, &request.code, &request.options(&config.lint_options)?)
+        }))
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+        result.map(|report| Response::new(report.into())).map_err(to_status)
+    }
+
+    async fn lint_batch(&self, request: Request<LintBatchRequest>) -> Result<Response<LintBatchResponse>, Status> {
+        self.admit(&request)?;
+        let files = request.into_inner().files;
+        info!("Received gRPC batch lint request for {} files", files.len());
+        if files.len() > self.config.max_batch_size {
+            return Err(Status::resource_exhausted(format!(
+                "Batch of {} files exceeds the limit of {}",
+                files.len(),
+                self.config.max_batch_size
+            )));
+        }
+        let config = self.config.clone();
+        let results = tokio::task::spawn_blocking(in_current_span(move || {
+            let span = tracing::Span::current();
+            files
+                .into_par_iter()
+                .map(|file| FileResult::from(span.in_scope(|| lint_batch_item(file.into(), &config.lint_options, config.max_input_bytes))))
+                .collect()
+        }))
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(LintBatchResponse { results }))
+    }
+
+    type LintStreamStream = LintStreamResult;
+
+    async fn lint_stream(&self, request: Request<Streaming<LintStreamRequest>>) -> Result<Response<Self::LintStreamStream>, Status> {
+        self.admit(&request)?;
+        let mut inbound = request.into_inner();
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        let options = self.config.lint_options.clone();
+        let max_bytes = self.config.max_input_bytes;
+        info!("Opened gRPC live-lint session");
+
+        tokio::spawn(async move {
+            let mut session = Some(Session::new(options.clone(), max_bytes));
+            loop {
+                let message = match inbound.message().await {
+                    Ok(Some(message)) => message,
+                    Ok(None) => break,
+                    Err(status) => {
+                        let _ = tx.send(Err(status)).await;
+                        break;
+                    }
+                };
+                let mut current = session.take().unwrap_or_else(|| Session::new(options.clone(), max_bytes));
+                let result = match client_message(message) {
+                    Ok(message) => match tokio::task::spawn_blocking(in_current_span(move || {
+                        let result = current.handle(message).map_err(linter_error_message);
+                        (current, result)
+                    }))
+                    .await
+                    {
+                        Ok((current, result)) => {
+                            session = Some(current);
+                            result.map(LintResponse::from)
+                        }
+                        Err(e) => Err(e.to_string()),
+                    },
+                    Err(e) => {
+                        session = Some(current);
+                        Err(format!("Invalid message: {}", e))
+                    }
+                };
+                let version = session.as_ref().map_or(0, Session::version);
+                let reply = LintStreamResponse {
+                    version,
+                    result: Some(match result {
+                        Ok(diagnostics) => lint_stream_response::Result::Diagnostics(diagnostics),
+                        Err(message) => lint_stream_response::Result::Error(message),
+                    }),
+                };
+                if tx.send(Ok(reply)).await.is_err() {
+                    break;
+                }
+            }
+            info!("Closed gRPC live-lint session");
+        }.instrument(tracing::Span::current()));
+
+        let replies = futures_util::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|reply| (reply, rx)) });
+        Ok(Response::new(Box::pin(replies)))
+    }
+}
+
+/// The TLS configuration of the gRPC server. Without the `tls` feature there is none to give.
+#[cfg(feature = "tls")]
+pub type TlsConfig = Arc<rustls::ServerConfig>;
+#[cfg(not(feature = "tls"))]
+pub type TlsConfig = std::convert::Infallible;
+
+/// How long a client may take to complete the TLS handshake.
+#[cfg(feature = "tls")]
+const TLS_HANDSHAKE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Accepts connections on `addr` and yields those that complete a TLS handshake. Handshakes
+/// run concurrently, so a slow client does not hold up the others.
+#[cfg(feature = "tls")]
+async fn tls_incoming(
+    addr: SocketAddr,
+    tls: TlsConfig,
+) -> std::io::Result<impl Stream<Item = std::io::Result<tokio_rustls::server::TlsStream<tokio::net::TcpStream>>>> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let acceptor = tokio_rustls::TlsAcceptor::from(tls);
+    let (tx, rx) = tokio::sync::mpsc::channel(16);
+    tokio::spawn(async move {
+        while !tx.is_closed() {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::warn!("Failed to accept gRPC connection: {}", e);
+                    continue;
+                }
+            };
+            let (acceptor, tx) = (acceptor.clone(), tx.clone());
+            tokio::spawn(async move {
+                match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => {
+                        let _ = tx.send(Ok(stream)).await;
+                    }
+                    Ok(Err(e)) => tracing::debug!("gRPC TLS handshake failed: {}", e),
+                    Err(_) => tracing::debug!("gRPC TLS handshake timed out"),
+                }
+            });
+        }
+    });
+    Ok(futures_util::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|stream| (stream, rx)) }))
+}
+
+/// Serves the gRPC API on `addr`, over TLS if `tls` is given, until `shutdown` resolves, then
+/// lets in-flight calls finish.
+pub async fn serve(
+    addr: SocketAddr,
+    tls: Option<TlsConfig>,
+    config: Arc<Config>,
+    access: Arc<Access>,
+    shutdown: impl std::future::Future<Output = ()>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let max_message_size = config.max_bulk_bytes.max(config.max_input_bytes);
+    let service = LinterServer::new(LinterService { config, access }).max_decoding_message_size(max_message_size);
+    let server = tonic::transport::Server::builder()
+        .trace_fn(|request| {
+            let header = request.headers().get(REQUEST_ID_HEADER.as_str()).and_then(|v| v.to_str().ok());
+            let request_id = request_id::accepted(header).map_or_else(request_id::generate, str::to_string);
+            tracing::info_span!("grpc_request", request_id = %request_id, rpc.method = %request.uri().path())
+        })
+        .add_service(service);
+    match tls {
+        #[cfg(feature = "tls")]
+        Some(tls) => {
+            info!("Serving gRPC with TLS on {}", addr);
+            server.serve_with_incoming_shutdown(tls_incoming(addr, tls).await?, shutdown).await?;
+        }
+        #[cfg(not(feature = "tls"))]
+        Some(never) => match never {},
+        None => {
+            info!("Serving gRPC on {}", addr);
+            server.serve_with_shutdown(addr, shutdown).await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+//! Liveness, readiness and version endpoints for orchestrators.
+//!
+//! `GET /healthz` answers as soon as the server is accepting requests. `GET /readyz` also
+//! loads every grammar and lints a canary snippet with it, answering `503 Service Unavailable`
+//! if any language fails. `GET /version` reports what this build lints with.
+
+use actix_web::{web, HttpResponse};
+use lintymclintface::{linters, LintOptions, SUPPORTED_LANGUAGES};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A snippet per language with a known problem, so that a ready linter reports diagnostics.
+const CANARIES: &[(&str, &str)] = &[
+    ("java", "class Canary {\n    int x = 1\n}\n"),
+    ("python", "def canary(:\n    pass\n"),
+    ("r", "x <- 1\n"),
+];
+
+#[derive(Serialize)]
+struct LanguageInfo {
+    grammar_version: &'static str,
+    rules: &'static [&'static str],
+}
+
+#[derive(Serialize)]
+struct VersionInfo {
+    version: &'static str,
+    languages: BTreeMap<&'static str, LanguageInfo>,
+}
+
+fn grammar_version(language: &str) -> &'static str {
+    match language {
+        "java" => env!("GRAMMAR_VERSION_JAVA"),
+        "python" => env!("GRAMMAR_VERSION_PYTHON"),
+        "r" => env!("GRAMMAR_VERSION_R"),
+        _ => "unknown",
+    }
+}
+
+pub async fn healthz() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({ "status": "ok" }))
+}
+
+/// Checks that `language`'s grammar loads and that linting its canary with `options`, as
+/// requests are linted, finds the planted problem. The canary is not subject to
+/// `--max-diagnostics`, which could otherwise hide the problem.
+fn check_language(language: &str, options: &LintOptions) -> Result<(), String> {
+    let code = CANARIES
+        .iter()
+        .find(|(lang, _)| *lang == language)
+        .map(|(_, code)| *code)
+        .ok_or_else(|| "no canary snippet".to_string())?;
+    linters::grammar(language).map_err(|e| format!("grammar failed to load: {:?}", e))?;
+    let options = LintOptions { max_diagnostics: None, ..options.clone() };
+    match lintymclintface::lint_with_options(language, code, &options).map(|report| report.errors) {
+        Ok(errors) if errors.is_empty() => Err("canary snippet produced no diagnostics".to_string()),
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("canary snippet failed to lint: {:?}", e)),
+    }
+}
+
+pub async fn readyz(config: web::Data<super::Config>) -> HttpResponse {
+    let options = config.lint_options.clone();
+    let checks = web::block(move || {
+        SUPPORTED_LANGUAGES
+            .iter()
+            .map(|&language| (language, check_language(language, &options)))
+            .collect::<Vec<_>>()
+    })
+    .await;
+    let Ok(checks) = checks else {
+        return HttpResponse::ServiceUnavailable().json(serde_json::json!({ "status": "unavailable" }));
+    };
+
+    let ready = checks.iter().all(|(_, result)| result.is_ok());
+    let languages: BTreeMap<&str, String> = checks
+        .into_iter()
+        .map(|(language, result)| (language, result.err().unwrap_or_else(|| "ok".to_string())))
+        .collect();
+    if ready {
+        HttpResponse::Ok().json(serde_json::json!({ "status": "ready", "languages": languages }))
+    } else {
+        HttpResponse::ServiceUnavailable().json(serde_json::json!({ "status": "unavailable", "languages": languages }))
+    }
+}
+
+pub async fn version() -> HttpResponse {
+    let languages = SUPPORTED_LANGUAGES
+        .iter()
+        .map(|&language| {
+            let rules = linters::rules(language).unwrap_or_default();
+            (language, LanguageInfo { grammar_version: grammar_version(language), rules })
+        })
+        .collect();
+    HttpResponse::Ok().json(VersionInfo { version: env!("CARGO_PKG_VERSION"), languages })
+}
@@ -0,0 +1,307 @@
+//! Asynchronous lint jobs for inputs too large to lint within one HTTP request.
+//!
+//! `POST /jobs` queues a job and returns its ID, `GET /jobs/{id}` reports progress and,
+//! once finished, the results, and `DELETE /jobs/{id}` cancels and forgets a job.
+//! `GET /jobs/{id}/events` streams the results as Server-Sent Events while the job runs.
+//! Finished jobs are dropped once they are older than the configured TTL.
+
+use super::{archive, archive_items, lint_batch_item, BatchItem, BatchItemResult};
+use lintymclintface::LintOptions;
+use actix_web::web::Bytes;
+use futures_util::Stream;
+use rayon::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::info;
+
+/// What a job lints.
+pub enum JobInput {
+    /// Files in the same form as a `POST /lint/batch` body.
+    Files(Vec<BatchItem>),
+    /// A `.tar.gz` or `.zip` archive, unpacked when the job starts.
+    Archive(Vec<u8>, archive::Limits),
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+struct JobState {
+    status: JobStatus,
+    total: usize,
+    /// One slot per file, in input order, filled in as files finish.
+    results: Vec<Option<BatchItemResult>>,
+    /// Indices into `results`, in the order the files finished.
+    completion_order: Vec<usize>,
+    /// Set when the whole job failed, e.g. because the archive could not be unpacked.
+    error: Option<String>,
+    finished_at: Option<Instant>,
+}
+
+pub struct Job {
+    pub id: String,
+    completed: AtomicUsize,
+    /// Non-zero once the job is cancelled; also aborts the parse in progress.
+    cancelled: Arc<AtomicUsize>,
+    state: Mutex<JobState>,
+    /// Bumped whenever a file finishes or the job ends, to wake event streams.
+    updates: watch::Sender<()>,
+}
+
+/// How many of the job's results are in, counting skipped archive entries, which are
+/// complete from the start.
+#[derive(Serialize, ToSchema)]
+struct Progress {
+    completed: usize,
+    total: usize,
+}
+
+/// The JSON representation of a job returned by the `/jobs` endpoints.
+#[derive(Serialize, ToSchema)]
+pub struct JobView<'a> {
+    id: &'a str,
+    status: JobStatus,
+    progress: Progress,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+    /// Present once the job has completed, in input order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    results: Option<Vec<&'a BatchItemResult>>,
+}
+
+impl Job {
+    /// Renders the job's current state with `f`, holding the lock only for that long.
+    pub fn with_view<T>(&self, f: impl FnOnce(JobView<'_>) -> T) -> T {
+        let state = self.state.lock().unwrap();
+        let results = (state.status == JobStatus::Completed)
+            .then(|| state.results.iter().flatten().collect());
+        f(JobView {
+            id: &self.id,
+            status: state.status,
+            progress: Progress { completed: self.completed.load(Ordering::Relaxed), total: state.total },
+            error: state.error.as_deref(),
+            results,
+        })
+    }
+
+    fn run(&self, input: JobInput, mut options: LintOptions, max_input_bytes: usize) {
+        options.cancellation = Some(self.cancelled.clone());
+        let items = match input {
+            JobInput::Files(items) => items,
+            JobInput::Archive(data, limits) => match archive_items(&data, limits) {
+                Ok((items, skipped)) => {
+                    // Skipped archive entries are reported as per-file errors so that every
+                    // path in the archive appears in the results.
+                    let mut state = self.state.lock().unwrap();
+                    for (path, reason) in skipped {
+                        let index = state.results.len();
+                        state.results.push(Some(BatchItemResult { path, language: None, errors: Vec::new(), truncated: false, error: Some(reason) }));
+                        state.completion_order.push(index);
+                        self.completed.fetch_add(1, Ordering::Relaxed);
+                    }
+                    items
+                }
+                Err(e) => return self.finish(JobStatus::Failed, Some(e.to_string())),
+            },
+        };
+
+        let offset = {
+            let mut state = self.state.lock().unwrap();
+            state.status = JobStatus::Running;
+            let offset = state.results.len();
+            state.total = offset + items.len();
+            state.results.resize_with(offset + items.len(), || None);
+            offset
+        };
+
+        let span = tracing::Span::current();
+        items.into_par_iter().enumerate().for_each(|(i, item)| {
+            if self.is_cancelled() {
+                return;
+            }
+            let result = span.in_scope(|| lint_batch_item(item, &options, max_input_bytes));
+            let mut state = self.state.lock().unwrap();
+            state.results[offset + i] = Some(result);
+            state.completion_order.push(offset + i);
+            self.completed.fetch_add(1, Ordering::Relaxed);
+            drop(state);
+            self.updates.send_replace(());
+        });
+
+        if self.is_cancelled() {
+            self.finish(JobStatus::Cancelled, None);
+        } else {
+            self.finish(JobStatus::Completed, None);
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) != 0
+    }
+
+    fn finish(&self, status: JobStatus, error: Option<String>) {
+        let mut state = self.state.lock().unwrap();
+        state.status = status;
+        state.error = error;
+        state.finished_at = Some(Instant::now());
+        drop(state);
+        self.updates.send_replace(());
+        info!("Job {} finished with status {:?}", self.id, status);
+    }
+}
+
+fn is_finished(status: JobStatus) -> bool {
+    !matches!(status, JobStatus::Queued | JobStatus::Running)
+}
+
+/// Streams a job as Server-Sent Events: a `result` event for every file, including files
+/// that finished before the stream was opened, followed by a final `status` event with the
+/// job's outcome (without the results).
+pub fn events(job: Arc<Job>) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+    // Subscribe before reading any state so that no update can be missed.
+    let updates = job.updates.subscribe();
+    futures_util::stream::unfold((job, updates, 0usize, false), |(job, mut updates, mut cursor, done)| async move {
+        if done {
+            return None;
+        }
+        loop {
+            let mut chunk = String::new();
+            let finished = {
+                let state = job.state.lock().unwrap();
+                for &index in &state.completion_order[cursor..] {
+                    if let Some(result) = &state.results[index] {
+                        let data = serde_json::to_string(result).unwrap_or_default();
+                        chunk.push_str(&format!("event: result\ndata: {}\n\n", data));
+                    }
+                }
+                cursor = state.completion_order.len();
+                is_finished(state.status)
+            };
+            if finished {
+                let status = job.with_view(|mut view| {
+                    view.results = None;
+                    serde_json::to_string(&view).unwrap_or_default()
+                });
+                chunk.push_str(&format!("event: status\ndata: {}\n\n", status));
+            }
+            if !chunk.is_empty() {
+                return Some((Ok(Bytes::from(chunk)), (job, updates, cursor, finished)));
+            }
+            if updates.changed().await.is_err() {
+                return None;
+            }
+        }
+    })
+}
+
+/// All known jobs, keyed by ID.
+pub struct JobStore {
+    jobs: Mutex<HashMap<String, Arc<Job>>>,
+    ttl: Duration,
+    /// Applied to every file linted by a job.
+    options: LintOptions,
+    /// Files larger than this are reported as errors instead of being linted.
+    max_input_bytes: usize,
+    /// Jobs queued or running, which may not exceed `max_active`.
+    active: Arc<AtomicUsize>,
+    max_active: usize,
+}
+
+/// Frees a job's slot in the store once the job has run, even if linting panicked.
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl JobStore {
+    pub fn new(ttl: Duration, options: LintOptions, max_active: usize, max_input_bytes: usize) -> Self {
+        JobStore {
+            jobs: Mutex::new(HashMap::new()),
+            ttl,
+            options,
+            max_input_bytes,
+            active: Arc::new(AtomicUsize::new(0)),
+            max_active,
+        }
+    }
+
+    /// Queues a job on the lint thread pool, or returns `None` if `max_active` jobs are
+    /// already queued or running.
+    pub fn submit(&self, input: JobInput) -> Option<Arc<Job>> {
+        let claimed = self.active.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |active| {
+            (active < self.max_active).then_some(active + 1)
+        });
+        if claimed.is_err() {
+            return None;
+        }
+        let slot = Slot(self.active.clone());
+        let total = match &input {
+            JobInput::Files(items) => items.len(),
+            JobInput::Archive(..) => 0,
+        };
+        let job = Arc::new(Job {
+            id: uuid::Uuid::new_v4().to_string(),
+            completed: AtomicUsize::new(0),
+            cancelled: Arc::new(AtomicUsize::new(0)),
+            state: Mutex::new(JobState {
+                status: JobStatus::Queued,
+                total,
+                results: Vec::new(),
+                completion_order: Vec::new(),
+                error: None,
+                finished_at: None,
+            }),
+            updates: watch::channel(()).0,
+        });
+        self.purge_expired();
+        self.jobs.lock().unwrap().insert(job.id.clone(), job.clone());
+        info!("Queued job {}", job.id);
+
+        let runner = job.clone();
+        let options = self.options.clone();
+        let max_input_bytes = self.max_input_bytes;
+        rayon::spawn(super::in_current_span(move || {
+            let _slot = slot;
+            runner.run(input, options, max_input_bytes)
+        }));
+        Some(job)
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<Job>> {
+        self.purge_expired();
+        self.jobs.lock().unwrap().get(id).cloned()
+    }
+
+    /// Cancels a job if it is still running and forgets it. Returns `false` for unknown IDs.
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.jobs.lock().unwrap().remove(id) {
+            Some(job) => {
+                job.cancelled.store(1, Ordering::Relaxed);
+                info!("Cancelled job {}", id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn purge_expired(&self) {
+        let ttl = self.ttl;
+        self.jobs.lock().unwrap().retain(|_, job| {
+            job.state.lock().unwrap().finished_at.is_none_or(|finished| finished.elapsed() < ttl)
+        });
+    }
+}
@@ -0,0 +1,174 @@
+//! Live-lint sessions over a WebSocket at `/ws`.
+//!
+//! Each connection holds one open [`Document`]. Messages are JSON text frames:
+//!
+//! * `{"type": "open", "language": "java", "text": "..."}` opens (or replaces) the document.
+//! * `{"type": "edit", "edits": [{"start": {"line": 1, "column": 1}, "end": {...}, "text": "..."}]}`
+//!   applies edits in order; positions use the same 1-based line and byte column as diagnostics.
+//! * `{"type": "close"}` ends the session.
+//!
+//! After every `open` and `edit` the server answers with
+//! `{"type": "diagnostics", "version": n, "diagnostics": [...]}`, or with
+//! `{"type": "error", "message": "..."}` if the message could not be applied. Diagnostics cut
+//! short by `--max-diagnostics` are marked with `"truncated": true`.
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_ws::AggregatedMessage;
+use lintymclintface::document::{Document, TextEdit};
+use lintymclintface::{LintOptions, LintReport, LinterError, SyntaxError};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, Instrument};
+
+use super::Config;
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(super) enum ClientMessage {
+    Open { language: String, text: String },
+    Edit { edits: Vec<TextEdit> },
+    Close,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ServerMessage {
+    Diagnostics {
+        version: u64,
+        diagnostics: Vec<SyntaxError>,
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        truncated: bool,
+    },
+    Error { message: String },
+}
+
+/// The state of one WebSocket connection, or of one gRPC `LintStream` call.
+#[derive(Default)]
+pub(super) struct Session {
+    document: Option<Document>,
+    version: u64,
+    options: LintOptions,
+    /// Largest the document may be when opened or grow to through edits.
+    max_bytes: usize,
+}
+
+impl Session {
+    pub(super) fn new(options: LintOptions, max_bytes: usize) -> Self {
+        Session { options, max_bytes, ..Session::default() }
+    }
+
+    /// The number of messages handled so far.
+    pub(super) fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Applies a client message. Parsing is CPU-bound, so this runs on the blocking pool.
+    pub(super) fn handle(&mut self, message: ClientMessage) -> Result<LintReport, LinterError> {
+        match message {
+            ClientMessage::Open { language, text } => {
+                if text.len() > self.max_bytes {
+                    return Err(LinterError::Io(format!("The document is {} bytes, exceeding the limit of {}", text.len(), self.max_bytes)));
+                }
+                self.document = Some(Document::open_with_options(&language, text, self.options.clone())?);
+            }
+            ClientMessage::Edit { edits } => {
+                let document = self
+                    .document
+                    .as_mut()
+                    .ok_or_else(|| LinterError::Parse("No document is open; send an \"open\" message first".to_string()))?;
+                for edit in &edits {
+                    let len = document.len_after(edit)?;
+                    if len > self.max_bytes {
+                        return Err(LinterError::Io(format!(
+                            "The edit would make the document {} bytes, exceeding the limit of {}",
+                            len, self.max_bytes
+                        )));
+                    }
+                    document.apply(edit)?;
+                }
+            }
+            ClientMessage::Close => return Err(LinterError::Parse("The session is closed".to_string())),
+        }
+        self.version += 1;
+        self.document.as_ref().map_or(Ok(LintReport::default()), Document::report)
+    }
+}
+
+fn error_message(e: LinterError) -> String {
+    match e {
+        LinterError::UnsupportedLanguage(msg) => format!("Unsupported language: {}", msg),
+        LinterError::Io(msg)
+        | LinterError::Parse(msg)
+        | LinterError::TreeSitterParseError(msg)
+        | LinterError::Timeout(msg)
+        | LinterError::InvalidOption(msg) => msg,
+    }
+}
+
+/// Upgrades the connection and serves a live-lint session until the client disconnects.
+///
+/// Messages larger than `--max-input-bytes` end the session, and edits that would grow the
+/// document past it are refused.
+pub async fn live_lint(req: HttpRequest, body: web::Payload, config: web::Data<Config>) -> actix_web::Result<HttpResponse> {
+    let (response, mut ws, stream) = actix_ws::handle(&req, body)?;
+    let mut stream = stream
+        .max_frame_size(config.max_input_bytes)
+        .aggregate_continuations()
+        .max_continuation_size(config.max_input_bytes);
+    let options = config.lint_options.clone();
+    let max_bytes = config.max_input_bytes;
+    info!("Opened live-lint session");
+
+    actix_web::rt::spawn(async move {
+        let new_session = move || Session::new(options.clone(), max_bytes);
+        let mut session = Some(new_session());
+        while let Some(Ok(message)) = stream.recv().await {
+            let text = match message {
+                AggregatedMessage::Text(text) => text,
+                AggregatedMessage::Ping(bytes) => {
+                    if ws.pong(&bytes).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                AggregatedMessage::Close(reason) => {
+                    let _ = ws.close(reason).await;
+                    info!("Closed live-lint session");
+                    return;
+                }
+                _ => continue,
+            };
+
+            let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                Ok(ClientMessage::Close) => break,
+                Ok(message) => {
+                    let mut current = session.take().unwrap_or_else(&new_session);
+                    match web::block(super::in_current_span(move || {
+                        let result = current.handle(message);
+                        (current, result)
+                    }))
+                    .await
+                    {
+                        Ok((current, result)) => {
+                            let version = current.version();
+                            session = Some(current);
+                            match result {
+                                Ok(report) => ServerMessage::Diagnostics { version, diagnostics: report.errors, truncated: report.truncated },
+                                Err(e) => ServerMessage::Error { message: error_message(e) },
+                            }
+                        }
+                        Err(e) => ServerMessage::Error { message: e.to_string() },
+                    }
+                }
+                Err(e) => ServerMessage::Error { message: format!("Invalid message: {}", e) },
+            };
+            debug!("Sending live-lint reply");
+            if ws.text(serde_json::to_string(&reply).unwrap_or_default()).await.is_err() {
+                break;
+            }
+        }
+        let _ = ws.close(None).await;
+        info!("Closed live-lint session");
+    }.instrument(tracing::Span::current()));
+
+    Ok(response)
+}
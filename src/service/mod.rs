@@ -0,0 +1,1058 @@
+//! The HTTP service started with `--service`.
+
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::info;
+use utoipa::ToSchema;
+
+use prometheus::{Encoder, TextEncoder, Gauge, Counter, Opts, Registry, IntCounterVec, IntGauge, HistogramOpts, HistogramVec};
+use lazy_static::lazy_static;
+use std::time::Instant;
+
+use lintymclintface::output::{self, OutputFormat};
+use lintymclintface::{LintOptions, LintReport, SyntaxError, SyntaxNode, LinterError, Severity};
+
+mod archive;
+mod auth;
+mod cache;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod health;
+mod jobs;
+mod live;
+mod openapi;
+mod playground;
+mod request_id;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+#[cfg(feature = "tls")]
+mod tls;
+
+use jobs::{JobInput, JobStore};
+
+/// The body of a `POST /v1/lint` request. Besides `code`, only `language` or a `filename`
+/// to detect it from is required; the other fields narrow down what is reported.
+#[derive(Deserialize, ToSchema)]
+struct LintRequest {
+    /// The language of `code`: java, python or r. Detected from `filename` when omitted.
+    language: Option<String>,
+    code: String,
+    /// The name of the file `code` comes from, used to detect the language and in `unix`
+    /// and `ndjson` output.
+    filename: Option<String>,
+    /// Report only these rules, by ID (`java/missing-token`) or name (`missing-token`).
+    rules: Option<Vec<String>>,
+    /// Never report these rules.
+    #[serde(default)]
+    disable: Vec<String>,
+    /// Leave out diagnostics less serious than this.
+    min_severity: Option<Severity>,
+    /// Also report syntax this version of the language does not support, e.g. `8` for
+    /// Java, `3.6` for Python or `4.0` for R.
+    #[schema(example = "3.8")]
+    target_version: Option<String>,
+    /// How to return the diagnostics: `json` (the default) for an array, `unix` for
+    /// `filename:line:col: severity: message [rule]` lines or `ndjson` for one JSON record.
+    format: Option<OutputFormat>,
+}
+
+impl LintRequest {
+    /// The language given, or else the one detected from `filename`.
+    fn language(&self) -> Result<&str, LinterError> {
+        if let Some(language) = &self.language {
+            return Ok(language);
+        }
+        self.filename
+            .as_deref()
+            .and_then(|filename| lintymclintface::detect_language(Path::new(filename)))
+            .ok_or_else(|| LinterError::InvalidOption("Cannot detect the language from the filename; pass \"language\" explicitly".to_string()))
+    }
+
+    /// The service's `options` with the rule selection of this request applied.
+    fn options(&self, options: &LintOptions) -> Result<LintOptions, LinterError> {
+        let target_version = self
+            .target_version
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+            .map_err(LinterError::InvalidOption)?;
+        Ok(LintOptions {
+            rules: self.rules.clone(),
+            disable: self.disable.clone(),
+            min_severity: self.min_severity,
+            target_version,
+            ..options.clone()
+        })
+    }
+}
+
+/// One file in a `POST /lint/batch` request.
+#[derive(Deserialize, ToSchema)]
+struct BatchItem {
+    path: String,
+    /// Detected from the extension of `path` when omitted.
+    language: Option<String>,
+    code: String,
+}
+
+/// The result for one file in a `POST /lint/batch` request.
+#[derive(Serialize, ToSchema)]
+struct BatchItemResult {
+    path: String,
+    language: Option<String>,
+    errors: Vec<SyntaxError>,
+    /// Whether `errors` was cut short by `--max-diagnostics`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    truncated: bool,
+    /// Set when the file could not be linted at all, e.g. for an unsupported language or
+    /// code larger than `--max-input-bytes`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// The result for one file in a `POST /lint/archive` report.
+#[derive(Serialize, ToSchema)]
+struct ArchiveFileResult {
+    language: String,
+    errors: Vec<SyntaxError>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct ArchiveSummary {
+    files_linted: usize,
+    files_skipped: usize,
+    errors: usize,
+}
+
+/// The aggregated report returned by `POST /lint/archive`, keyed by path within the archive.
+#[derive(Serialize, ToSchema)]
+struct ArchiveReport {
+    files: BTreeMap<String, ArchiveFileResult>,
+    /// Files that were not linted, with the reason why.
+    skipped: BTreeMap<String, String>,
+    summary: ArchiveSummary,
+}
+
+/// Limits for the HTTP service, set from the command line.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Address of the interface to listen on; `0.0.0.0` or `::` for all of them.
+    pub bind: IpAddr,
+    pub port: u16,
+    /// Unix domain socket to listen on instead of a TCP port.
+    pub unix_socket: Option<PathBuf>,
+    /// PEM certificate chain and private key; when set, the service is served over TLS.
+    #[cfg(feature = "tls")]
+    pub tls_cert: Option<PathBuf>,
+    #[cfg(feature = "tls")]
+    pub tls_key: Option<PathBuf>,
+    /// Maximum number of files accepted by `POST /lint/batch`.
+    pub max_batch_size: usize,
+    /// Maximum total decompressed size of an archive sent to `POST /lint/archive`.
+    pub max_archive_bytes: u64,
+    /// Maximum number of files in an archive sent to `POST /lint/archive`.
+    pub max_archive_files: usize,
+    /// How long the results of a finished job are kept.
+    pub job_ttl: Duration,
+    /// Maximum number of jobs queued or running at once.
+    pub max_jobs: usize,
+    /// Maximum size of a `POST /lint` body, a live-lint message, or any one file in a batch,
+    /// archive or job.
+    pub max_input_bytes: usize,
+    /// Maximum size of a batch, archive or job request body.
+    pub max_bulk_bytes: usize,
+    /// Timeout and diagnostic cap applied to every file linted.
+    pub lint_options: LintOptions,
+    /// File of `<client> <key>` lines; when set, requests must present one of the keys.
+    pub api_keys_file: Option<PathBuf>,
+    /// Requests per second allowed for each client, if rate limiting is enabled.
+    pub rate_limit: Option<f64>,
+    /// Number of requests a client may make at once before being rate limited.
+    pub rate_limit_burst: u32,
+    /// How long in-flight requests may take to finish after SIGTERM or SIGINT.
+    pub shutdown_grace: Duration,
+    /// Port for the gRPC API, if it should be served.
+    #[cfg(feature = "grpc")]
+    pub grpc_port: Option<u16>,
+    /// Number of lint results kept in memory for repeated inputs; 0 disables the cache.
+    pub cache_entries: usize,
+    /// Origins allowed to make cross-origin requests; `*` allows any.
+    pub cors_origins: Vec<String>,
+}
+
+impl Config {
+    /// Loads the certificate for `--tls-cert`, if one is configured.
+    #[cfg(feature = "tls")]
+    fn tls(&self) -> std::io::Result<Option<std::sync::Arc<tls::CertificateResolver>>> {
+        match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => Ok(Some(std::sync::Arc::new(tls::CertificateResolver::load(cert, key)?))),
+            (None, None) => Ok(None),
+            _ => Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "--tls-cert and --tls-key must be given together")),
+        }
+    }
+
+    fn archive_limits(&self) -> archive::Limits {
+        archive::Limits { max_bytes: self.max_archive_bytes, max_files: self.max_archive_files }
+    }
+}
+
+/// Prefix of the current version of the lint API. Operational endpoints such as `/metrics`
+/// and `/healthz` are not versioned.
+const API_PREFIX: &str = "/v1";
+
+/// Content type of newline-delimited JSON streams.
+const NDJSON: &str = "application/x-ndjson";
+
+/// Histogram buckets for lint latencies, in seconds: from half a millisecond for small
+/// files up to the default parse timeout of ten seconds.
+const LATENCY_BUCKETS: &[f64] = &[0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+// --- Prometheus Metrics --- 
+lazy_static! {
+    pub static ref REGISTRY: Registry = Registry::new();
+    pub static ref LINT_REQUESTS_TOTAL: Counter = Counter::new(
+        "lint_requests_total",
+        "Total number of linting requests."
+    ).unwrap();
+    pub static ref LINT_REQUESTS_BY_LANGUAGE: IntCounterVec = IntCounterVec::new(
+        Opts::new("lint_requests_by_language", "Total number of linting requests by language."),
+        &["language"]
+    ).unwrap();
+    pub static ref LINT_REQUEST_DURATION_SECONDS: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("lint_request_duration_seconds", "Duration of linting a file in seconds, by language and outcome.")
+            .buckets(LATENCY_BUCKETS.to_vec()),
+        &["language", "outcome"]
+    ).unwrap();
+    pub static ref LINT_PARSE_DURATION_SECONDS: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("lint_parse_duration_seconds", "Time spent parsing a file in seconds, by language.")
+            .buckets(LATENCY_BUCKETS.to_vec()),
+        &["language"]
+    ).unwrap();
+    pub static ref LINT_CHECK_DURATION_SECONDS: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("lint_check_duration_seconds", "Time spent running the rules over a parsed file in seconds, by language.")
+            .buckets(LATENCY_BUCKETS.to_vec()),
+        &["language"]
+    ).unwrap();
+    pub static ref LINT_BYTES_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new("lint_bytes_total", "Total number of bytes of code linted, by language."),
+        &["language"]
+    ).unwrap();
+    pub static ref LINT_LINES_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new("lint_lines_total", "Total number of lines of code linted, by language."),
+        &["language"]
+    ).unwrap();
+    pub static ref LINT_UNSUPPORTED_LANGUAGE_TOTAL: Counter = Counter::new(
+        "lint_unsupported_language_total",
+        "Total number of linting requests rejected for an unsupported language."
+    ).unwrap();
+    pub static ref LINT_DIAGNOSTICS_BY_RULE: IntCounterVec = IntCounterVec::new(
+        Opts::new("lint_diagnostics_by_rule", "Total number of diagnostics reported, by rule."),
+        &["rule"]
+    ).unwrap();
+    pub static ref LINT_IN_FLIGHT: IntGauge = IntGauge::new(
+        "lint_in_flight",
+        "Number of files being linted right now."
+    ).unwrap();
+    pub static ref LINT_ERRORS_TOTAL: Counter = Counter::new(
+        "lint_errors_total",
+        "Total number of linting errors found."
+    ).unwrap();
+    pub static ref LINT_ERRORS_BY_LANGUAGE: IntCounterVec = IntCounterVec::new(
+        Opts::new("lint_errors_by_language", "Total number of linting errors by language."),
+        &["language"]
+    ).unwrap();
+    pub static ref LINT_LAST_FILE_ERRORS: Gauge = Gauge::new(
+        "lint_last_file_errors",
+        "Number of errors in the last processed file."
+    ).unwrap();
+    pub static ref LINT_CLIENT_REQUESTS_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new("lint_client_requests_total", "Total number of authenticated HTTP requests by client."),
+        &["client"]
+    ).unwrap();
+    pub static ref LINT_CACHE_HITS_TOTAL: Counter = Counter::new(
+        "lint_cache_hits_total",
+        "Total number of lints answered from the result cache."
+    ).unwrap();
+    pub static ref LINT_CACHE_MISSES_TOTAL: Counter = Counter::new(
+        "lint_cache_misses_total",
+        "Total number of lints not found in the result cache."
+    ).unwrap();
+    pub static ref LINT_RATE_LIMITED_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new("lint_rate_limited_total", "Total number of HTTP requests rejected by the rate limiter, by client."),
+        &["client"]
+    ).unwrap();
+}
+
+fn register_metrics() {
+    REGISTRY.register(Box::new(LINT_REQUESTS_TOTAL.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_REQUESTS_BY_LANGUAGE.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_REQUEST_DURATION_SECONDS.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_PARSE_DURATION_SECONDS.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_CHECK_DURATION_SECONDS.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_BYTES_TOTAL.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_LINES_TOTAL.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_IN_FLIGHT.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_UNSUPPORTED_LANGUAGE_TOTAL.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_DIAGNOSTICS_BY_RULE.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_ERRORS_TOTAL.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_ERRORS_BY_LANGUAGE.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_LAST_FILE_ERRORS.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_CLIENT_REQUESTS_TOTAL.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_RATE_LIMITED_TOTAL.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_CACHE_HITS_TOTAL.clone())).unwrap();
+    REGISTRY.register(Box::new(LINT_CACHE_MISSES_TOTAL.clone())).unwrap();
+}
+
+/// The value of the `language` label for `language`: the language itself if it is supported,
+/// or else `unknown`, so that clients cannot create new time series with made-up languages.
+fn language_label(language: &str) -> &'static str {
+    lintymclintface::SUPPORTED_LANGUAGES.iter().find(|l| **l == language).copied().unwrap_or("unknown")
+}
+
+/// Counts a lint in `lint_in_flight` until it is dropped, even if the lint panics.
+struct InFlight;
+
+impl InFlight {
+    fn start() -> Self {
+        LINT_IN_FLIGHT.inc();
+        InFlight
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        LINT_IN_FLIGHT.dec();
+    }
+}
+
+/// Lints `code`, through the result cache, and records the request in the Prometheus metrics.
+fn lint_and_record(language: &str, code: &str, options: &LintOptions) -> Result<LintReport, LinterError> {
+    let label = language_label(language);
+    LINT_REQUESTS_TOTAL.inc();
+    LINT_REQUESTS_BY_LANGUAGE.with_label_values(&[label]).inc();
+
+    LINT_BYTES_TOTAL.with_label_values(&[label]).inc_by(code.len() as u64);
+    LINT_LINES_TOTAL.with_label_values(&[label]).inc_by(code.lines().count() as u64);
+
+    let in_flight = InFlight::start();
+    let start_time = Instant::now();
+
+    let result = cache::lint(language, code, options);
+
+    let duration = start_time.elapsed().as_secs_f64();
+    drop(in_flight);
+    let outcome = match &result {
+        Ok((_, Some(timings))) => {
+            LINT_PARSE_DURATION_SECONDS.with_label_values(&[label]).observe(timings.parse.as_secs_f64());
+            LINT_CHECK_DURATION_SECONDS.with_label_values(&[label]).observe(timings.check.as_secs_f64());
+            "ok"
+        }
+        Ok((_, None)) => "cached",
+        Err(LinterError::Timeout(_)) => "timeout",
+        Err(LinterError::UnsupportedLanguage(_)) => {
+            LINT_UNSUPPORTED_LANGUAGE_TOTAL.inc();
+            "error"
+        }
+        Err(_) => "error",
+    };
+    LINT_REQUEST_DURATION_SECONDS.with_label_values(&[label, outcome]).observe(duration);
+
+    let result = result.map(|(report, _)| report);
+    match &result {
+        Ok(report) => {
+            LINT_ERRORS_TOTAL.inc_by(report.errors.len() as f64);
+            LINT_ERRORS_BY_LANGUAGE.with_label_values(&[label]).inc_by(report.errors.len() as u64);
+            LINT_LAST_FILE_ERRORS.set(report.errors.len() as f64);
+            for error in &report.errors {
+                LINT_DIAGNOSTICS_BY_RULE.with_label_values(&[&error.rule]).inc();
+            }
+        },
+        Err(_) => LINT_ERRORS_TOTAL.inc(), // Increment for linter errors themselves
+    }
+    result
+}
+
+fn linter_error_message(e: LinterError) -> String {
+    match e {
+        LinterError::Io(msg) => msg,
+        LinterError::Parse(msg) => msg,
+        LinterError::TreeSitterParseError(msg) => msg,
+        LinterError::UnsupportedLanguage(msg) => format!("Unsupported language: {}", msg),
+        LinterError::Timeout(msg) => msg,
+        LinterError::InvalidOption(msg) => msg,
+    }
+}
+
+/// Header set on a `POST /lint` response whose diagnostics were cut short by `--max-diagnostics`.
+const TRUNCATED_HEADER: &str = "X-Lint-Truncated";
+
+#[utoipa::path(
+    post,
+    path = "/v1/lint",
+    tag = "lint",
+    request_body = LintRequest,
+    responses(
+        (status = 200, description = "The diagnostics found; `X-Lint-Truncated: true` marks a list cut short by `--max-diagnostics`", body = Vec<SyntaxError>),
+        (status = 400, description = "An unknown rule, an invalid `target_version` or a language that cannot be detected", body = openapi::ApiError),
+        (status = 413, description = "The body exceeds `--max-input-bytes`", body = openapi::ApiError),
+        (status = 422, description = "Parsing timed out", body = Vec<SyntaxError>),
+    )
+)]
+/// Lints one file. Bodies over `--max-input-bytes` are rejected with `413 Payload Too Large`
+/// by the JSON extractor, invalid options get `400 Bad Request`, and parses that time out get
+/// `422 Unprocessable Entity`.
+async fn lint_service(req: web::Json<LintRequest>, config: web::Data<Config>) -> HttpResponse {
+    let (req, lint_options) = (req.into_inner(), config.lint_options.clone());
+    let linted = web::block(in_current_span(move || {
+        let result = req.language().and_then(|language| {
+            info!("Received lint request for language: {}", language);
+            lint_and_record(language, &req.code, &req.options(&lint_options)?)
+        });
+        (req, result)
+    }))
+    .await;
+    let (req, result) = match linted {
+        Ok(linted) => linted,
+        Err(e) => return HttpResponse::InternalServerError().json(openapi::ApiError { error: e.to_string() }),
+    };
+
+    match result {
+        Ok(report) => {
+            let mut response = HttpResponse::Ok();
+            if report.truncated {
+                response.insert_header((TRUNCATED_HEADER, "true"));
+            }
+            let path = req.filename.as_deref().unwrap_or("-");
+            match req.format.unwrap_or_default() {
+                OutputFormat::Json => response.json(report.errors),
+                format => {
+                    let content_type = if format == OutputFormat::Ndjson { NDJSON } else { "text/plain; charset=utf-8" };
+                    let mut body = output::render(format, path, &report).unwrap_or_default();
+                    if !body.is_empty() {
+                        body.push('\n');
+                    }
+                    response.content_type(content_type).body(body)
+                }
+            }
+        }
+        Err(LinterError::InvalidOption(msg)) => HttpResponse::BadRequest().json(openapi::ApiError { error: msg }),
+        Err(e) => {
+            let mut response = match e {
+                LinterError::Timeout(_) => HttpResponse::UnprocessableEntity(),
+                _ => HttpResponse::Ok(),
+            };
+            let error = SyntaxError { line: 0, column: 0, message: linter_error_message(e), severity: Severity::Error, rule: "linter-error".to_string() };
+            response.json(vec![error])
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/parse",
+    tag = "lint",
+    request_body = LintRequest,
+    responses(
+        (status = 200, description = "The syntax tree of the code", body = SyntaxNode),
+        (status = 400, description = "The language is not supported", body = openapi::ApiError),
+        (status = 413, description = "The body exceeds `--max-input-bytes`", body = openapi::ApiError),
+        (status = 422, description = "Parsing timed out", body = openapi::ApiError),
+    )
+)]
+/// Parses one file and returns its syntax tree, as shown by the playground at `/`.
+async fn parse_service(req: web::Json<LintRequest>, config: web::Data<Config>) -> HttpResponse {
+    let (req, lint_options) = (req.into_inner(), config.lint_options.clone());
+    let parsed = web::block(in_current_span(move || {
+        req.language().and_then(|language| lintymclintface::syntax_tree(language, &req.code, &lint_options))
+    }))
+    .await;
+    let result = match parsed {
+        Ok(result) => result,
+        Err(e) => return HttpResponse::InternalServerError().json(openapi::ApiError { error: e.to_string() }),
+    };
+    match result {
+        Ok(tree) => HttpResponse::Ok().json(tree),
+        Err(e) => {
+            let mut response = match e {
+                LinterError::Timeout(_) => HttpResponse::UnprocessableEntity(),
+                LinterError::UnsupportedLanguage(_) | LinterError::Parse(_) | LinterError::InvalidOption(_) => HttpResponse::BadRequest(),
+                _ => HttpResponse::InternalServerError(),
+            };
+            response.json(openapi::ApiError { error: linter_error_message(e) })
+        }
+    }
+}
+
+/// Lints one file of a batch, archive or job, refusing files larger than `max_input_bytes`.
+fn lint_batch_item(item: BatchItem, options: &LintOptions, max_input_bytes: usize) -> BatchItemResult {
+    if item.code.len() > max_input_bytes {
+        return BatchItemResult {
+            path: item.path,
+            language: item.language,
+            errors: Vec::new(),
+            truncated: false,
+            error: Some(format!("Code of {} bytes exceeds the limit of {}", item.code.len(), max_input_bytes)),
+        };
+    }
+    let language = item
+        .language
+        .or_else(|| lintymclintface::detect_language(Path::new(&item.path)).map(str::to_string));
+    let Some(lang) = language.as_deref() else {
+        return BatchItemResult {
+            path: item.path,
+            language: None,
+            errors: Vec::new(),
+            truncated: false,
+            error: Some("Cannot detect the language from the path; pass \"language\" explicitly".to_string()),
+        };
+    };
+    let (report, error) = match lint_and_record(lang, &item.code, options) {
+        Ok(report) => (report, None),
+        Err(e) => (LintReport::default(), Some(linter_error_message(e))),
+    };
+    BatchItemResult { path: item.path, language, errors: report.errors, truncated: report.truncated, error }
+}
+
+/// Whether the client asked for results as a stream of newline-delimited JSON.
+fn wants_ndjson(req: &HttpRequest) -> bool {
+    req.headers()
+        .get(actix_web::http::header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains(NDJSON))
+}
+
+/// Streams one NDJSON line per file as soon as it has been linted, so results arrive in
+/// completion order rather than request order. `done` results are sent first.
+fn stream_ndjson(items: Vec<BatchItem>, done: Vec<BatchItemResult>, options: LintOptions, max_input_bytes: usize) -> HttpResponse {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<BatchItemResult>();
+    for result in done {
+        let _ = tx.send(result);
+    }
+    rayon::spawn(in_current_span(move || {
+        let span = tracing::Span::current();
+        items.into_par_iter().for_each_with(tx, |tx, item| {
+            // Stop linting once the client has gone away.
+            if !tx.is_closed() {
+                let _ = tx.send(span.in_scope(|| lint_batch_item(item, &options, max_input_bytes)));
+            }
+        })
+    }));
+
+    let lines = futures_util::stream::unfold(rx, |mut rx| async move {
+        let result = rx.recv().await?;
+        let mut line = serde_json::to_vec(&result).unwrap_or_default();
+        line.push(b'\n');
+        Some((Ok::<_, actix_web::Error>(web::Bytes::from(line)), rx))
+    });
+    HttpResponse::Ok().content_type(NDJSON).streaming(lines)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/lint/batch",
+    tag = "lint",
+    request_body = Vec<BatchItem>,
+    responses(
+        (status = 200, description = "One result per file, in request order, or one per line in completion order with `Accept: application/x-ndjson`", content(
+            (Vec<BatchItemResult> = "application/json"),
+            (BatchItemResult = "application/x-ndjson"),
+        )),
+        (status = 413, description = "The batch has more files than `--max-batch-size`", body = openapi::ApiError),
+    )
+)]
+/// Lints many files in one request, in parallel, returning the results in request order,
+/// or streaming them as NDJSON when the client sends `Accept: application/x-ndjson`.
+async fn lint_batch_service(http_req: HttpRequest, req: web::Json<Vec<BatchItem>>, config: web::Data<Config>) -> HttpResponse {
+    let items = req.into_inner();
+    info!("Received batch lint request for {} files", items.len());
+    if items.len() > config.max_batch_size {
+        return HttpResponse::PayloadTooLarge().json(openapi::ApiError {
+            error: format!("Batch of {} files exceeds the limit of {}", items.len(), config.max_batch_size),
+        });
+    }
+
+    if wants_ndjson(&http_req) {
+        return stream_ndjson(items, Vec::new(), config.lint_options.clone(), config.max_input_bytes);
+    }
+
+    let options = config.lint_options.clone();
+    let max_input_bytes = config.max_input_bytes;
+    let span = tracing::Span::current();
+    match web::block(move || items.into_par_iter().map(|item| span.in_scope(|| lint_batch_item(item, &options, max_input_bytes))).collect::<Vec<_>>()).await {
+        Ok(results) => HttpResponse::Ok().json(results),
+        Err(e) => HttpResponse::InternalServerError().json(openapi::ApiError { error: e.to_string() }),
+    }
+}
+
+/// Unpacks an archive into batch items for every supported file, plus the skipped paths.
+fn archive_items(
+    data: &[u8],
+    limits: archive::Limits,
+) -> Result<(Vec<BatchItem>, BTreeMap<String, String>), archive::ArchiveError> {
+    let (entries, skipped_entries) = archive::extract(data, limits)?;
+    let mut skipped: BTreeMap<String, String> =
+        skipped_entries.into_iter().map(|s| (s.path, s.reason)).collect();
+
+    let mut items = Vec::new();
+    for entry in entries {
+        let Some(language) = lintymclintface::detect_language(Path::new(&entry.path)) else {
+            skipped.insert(entry.path, "unsupported file type".to_string());
+            continue;
+        };
+        match String::from_utf8(entry.contents) {
+            Ok(code) => items.push(BatchItem { path: entry.path, language: Some(language.to_string()), code }),
+            Err(_) => {
+                skipped.insert(entry.path, "not valid UTF-8".to_string());
+            }
+        }
+    }
+    Ok((items, skipped))
+}
+
+fn lint_archive(items: Vec<BatchItem>, skipped: BTreeMap<String, String>, options: &LintOptions, max_input_bytes: usize) -> ArchiveReport {
+    let span = tracing::Span::current();
+    let files: BTreeMap<String, ArchiveFileResult> = items
+        .into_par_iter()
+        .map(|item| span.in_scope(|| lint_batch_item(item, options, max_input_bytes)))
+        .map(|r| {
+            let file = ArchiveFileResult { language: r.language.unwrap_or_default(), errors: r.errors, truncated: r.truncated, error: r.error };
+            (r.path, file)
+        })
+        .collect();
+    let summary = ArchiveSummary {
+        files_linted: files.len(),
+        files_skipped: skipped.len(),
+        errors: files.values().map(|f| f.errors.len()).sum(),
+    };
+    ArchiveReport { files, skipped, summary }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/lint/archive",
+    tag = "lint",
+    request_body(content = Vec<u8>, description = "A `.tar.gz` or `.zip` archive", content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "A report keyed by path, or one result per line with `Accept: application/x-ndjson`", content(
+            (ArchiveReport = "application/json"),
+            (BatchItemResult = "application/x-ndjson"),
+        )),
+        (status = 400, description = "The archive could not be read", body = openapi::ApiError),
+        (status = 413, description = "The archive exceeds `--max-archive-bytes` or `--max-archive-files`", body = openapi::ApiError),
+    )
+)]
+/// Lints every supported file in an uploaded `.tar.gz` or `.zip` archive.
+///
+/// With `Accept: application/x-ndjson` the results are streamed one file per line instead,
+/// with skipped entries reported as per-file errors.
+async fn lint_archive_service(req: HttpRequest, body: web::Bytes, config: web::Data<Config>) -> HttpResponse {
+    info!("Received archive lint request of {} bytes", body.len());
+    let limits = config.archive_limits();
+    let ndjson = wants_ndjson(&req);
+    let options = config.lint_options.clone();
+    let max_input_bytes = config.max_input_bytes;
+
+    match web::block(in_current_span(move || archive_items(&body, limits))).await {
+        Ok(Ok((items, skipped))) if ndjson => {
+            let skipped = skipped
+                .into_iter()
+                .map(|(path, reason)| BatchItemResult { path, language: None, errors: Vec::new(), truncated: false, error: Some(reason) })
+                .collect();
+            stream_ndjson(items, skipped, options, max_input_bytes)
+        }
+        Ok(Ok((items, skipped))) => match web::block(in_current_span(move || lint_archive(items, skipped, &options, max_input_bytes))).await {
+            Ok(report) => HttpResponse::Ok().json(report),
+            Err(e) => HttpResponse::InternalServerError().json(openapi::ApiError { error: e.to_string() }),
+        },
+        Ok(Err(e @ archive::ArchiveError::TooLarge(_))) => {
+            HttpResponse::PayloadTooLarge().json(openapi::ApiError { error: e.to_string() })
+        }
+        Ok(Err(e)) => HttpResponse::BadRequest().json(openapi::ApiError { error: e.to_string() }),
+        Err(e) => HttpResponse::InternalServerError().json(openapi::ApiError { error: e.to_string() }),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/jobs",
+    tag = "jobs",
+    request_body(description = "A batch of files as for `POST /v1/lint/batch`, or an archive with any other content type", content(
+        (Vec<BatchItem> = "application/json"),
+        (Vec<u8> = "application/octet-stream"),
+    )),
+    responses(
+        (status = 202, description = "The job was queued; `Location` points at it", body = jobs::JobView),
+        (status = 400, description = "The batch is not valid JSON", body = openapi::ApiError),
+        (status = 413, description = "The batch has more files than `--max-batch-size`", body = openapi::ApiError),
+        (status = 503, description = "`--max-jobs` jobs are already queued or running; retry after the `Retry-After` delay", body = openapi::ApiError),
+    )
+)]
+/// Queues a job for a JSON batch body, or for an archive body with any other content type.
+async fn submit_job(
+    req: HttpRequest,
+    body: web::Bytes,
+    config: web::Data<Config>,
+    jobs: web::Data<JobStore>,
+) -> HttpResponse {
+    let is_json = req
+        .headers()
+        .get(actix_web::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+
+    let input = if is_json {
+        let items: Vec<BatchItem> = match serde_json::from_slice(&body) {
+            Ok(items) => items,
+            Err(e) => return HttpResponse::BadRequest().json(openapi::ApiError { error: e.to_string() }),
+        };
+        if items.len() > config.max_batch_size {
+            return HttpResponse::PayloadTooLarge().json(openapi::ApiError {
+            error: format!("Batch of {} files exceeds the limit of {}", items.len(), config.max_batch_size),
+        });
+        }
+        JobInput::Files(items)
+    } else {
+        JobInput::Archive(body.to_vec(), config.archive_limits())
+    };
+
+    let Some(job) = jobs.submit(input) else {
+        return HttpResponse::ServiceUnavailable()
+            .insert_header((actix_web::http::header::RETRY_AFTER, "1"))
+            .json(openapi::ApiError {
+                error: format!("{} jobs are already queued or running; try again later", config.max_jobs),
+            });
+    };
+    job.with_view(|view| {
+        HttpResponse::Accepted()
+            .insert_header((actix_web::http::header::LOCATION, format!("{}/{}", req.path().trim_end_matches('/'), job.id)))
+            .json(view)
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path, description = "The job ID")),
+    responses(
+        (status = 200, description = "The job's progress, and its results once completed", body = jobs::JobView),
+        (status = 404, description = "No such job, or it has expired", body = openapi::ApiError),
+    )
+)]
+async fn get_job(id: web::Path<String>, jobs: web::Data<JobStore>) -> HttpResponse {
+    match jobs.get(&id) {
+        Some(job) => job.with_view(|view| HttpResponse::Ok().json(view)),
+        None => HttpResponse::NotFound().json(openapi::ApiError { error: format!("No job with ID {}", id) }),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/jobs/{id}/events",
+    tag = "jobs",
+    params(("id" = String, Path, description = "The job ID")),
+    responses(
+        (status = 200, description = "A `result` event per file, then a final `status` event", content_type = "text/event-stream", body = String),
+        (status = 404, description = "No such job, or it has expired", body = openapi::ApiError),
+    )
+)]
+/// Streams a job's results as Server-Sent Events while it runs.
+async fn job_events(id: web::Path<String>, jobs: web::Data<JobStore>) -> HttpResponse {
+    match jobs.get(&id) {
+        Some(job) => HttpResponse::Ok()
+            .content_type("text/event-stream")
+            .insert_header((actix_web::http::header::CACHE_CONTROL, "no-cache"))
+            .streaming(jobs::events(job)),
+        None => HttpResponse::NotFound().json(openapi::ApiError { error: format!("No job with ID {}", id) }),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path, description = "The job ID")),
+    responses(
+        (status = 204, description = "The job was cancelled and forgotten"),
+        (status = 404, description = "No such job, or it has expired", body = openapi::ApiError),
+    )
+)]
+async fn cancel_job(id: web::Path<String>, jobs: web::Data<JobStore>) -> HttpResponse {
+    if jobs.cancel(&id) {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::NotFound().json(openapi::ApiError { error: format!("No job with ID {}", id) })
+    }
+}
+
+// New handler for /metrics endpoint
+async fn metrics() -> impl Responder {
+    let mut buffer = Vec::new();
+    let encoder = TextEncoder::new();
+    let metric_families = REGISTRY.gather();
+    encoder.encode(&metric_families, &mut buffer).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+/// Wraps `f` to run inside the span current now, for work handed to another thread, so
+/// that what it logs still carries the request ID.
+fn in_current_span<T>(f: impl FnOnce() -> T) -> impl FnOnce() -> T {
+    let span = tracing::Span::current();
+    move || span.in_scope(f)
+}
+
+/// Accepts JSON bodies of up to `limit` bytes, answering those it rejects with an
+/// [`openapi::ApiError`] like the handlers do.
+fn json_config(limit: usize) -> web::JsonConfig {
+    web::JsonConfig::default().limit(limit).error_handler(|err, _| {
+        let response = HttpResponse::build(actix_web::ResponseError::status_code(&err)).json(openapi::ApiError { error: err.to_string() });
+        actix_web::error::InternalError::from_response(err, response).into()
+    })
+}
+
+/// Registers the lint API routes, mounted both under [`API_PREFIX`] and at the root.
+fn api(cfg: &mut web::ServiceConfig, max_input_bytes: usize, max_bulk_bytes: usize) {
+    cfg.service(
+        web::resource("/lint")
+            .app_data(json_config(max_input_bytes))
+            .route(web::post().to(lint_service)),
+    )
+    .service(
+        web::resource("/parse")
+            .app_data(json_config(max_input_bytes))
+            .route(web::post().to(parse_service)),
+    )
+    .service(
+        web::resource("/lint/batch")
+            .app_data(json_config(max_bulk_bytes))
+            .route(web::post().to(lint_batch_service)),
+    )
+    .service(
+        web::resource("/lint/archive")
+            .app_data(web::PayloadConfig::new(max_bulk_bytes))
+            .route(web::post().to(lint_archive_service)),
+    )
+    .service(
+        web::resource("/jobs")
+            .app_data(web::PayloadConfig::new(max_bulk_bytes))
+            .route(web::post().to(submit_job)),
+    )
+    .service(
+        web::resource("/jobs/{id}")
+            .route(web::get().to(get_job))
+            .route(web::delete().to(cancel_job)),
+    )
+    .route("/jobs/{id}/events", web::get().to(job_events))
+    .route("/ws", web::get().to(live::live_lint));
+}
+
+/// Builds the CORS policy for `--cors-origin`. Without any origins, cross-origin requests
+/// get no CORS headers, so browsers keep them from reading the responses.
+fn cors(origins: &[String]) -> actix_cors::Cors {
+    let cors = actix_cors::Cors::default()
+        .allowed_methods(["GET", "POST", "DELETE"])
+        .allowed_headers([
+            actix_web::http::header::AUTHORIZATION,
+            actix_web::http::header::CONTENT_TYPE,
+            actix_web::http::header::ACCEPT,
+            actix_web::http::header::HeaderName::from_static("x-api-key"),
+            request_id::REQUEST_ID_HEADER,
+        ])
+        .expose_headers([
+            actix_web::http::header::HeaderName::from_static("x-lint-truncated"),
+            request_id::REQUEST_ID_HEADER,
+            actix_web::http::header::RETRY_AFTER,
+            actix_web::http::header::LOCATION,
+        ])
+        .max_age(3600);
+    if origins.iter().any(|origin| origin == "*") {
+        return cors.allow_any_origin();
+    }
+    origins.iter().fold(cors, |cors, origin| cors.allowed_origin(origin))
+}
+
+/// Where the HTTP service listens.
+enum Listener {
+    Tcp(SocketAddr),
+    #[cfg(feature = "tls")]
+    Tls(SocketAddr, Box<rustls::ServerConfig>),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl std::fmt::Display for Listener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Listener::Tcp(addr) => write!(f, "http://{}", addr),
+            #[cfg(feature = "tls")]
+            Listener::Tls(addr, _) => write!(f, "https://{}", addr),
+            #[cfg(unix)]
+            Listener::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Removes a socket left behind by an earlier run, so that binding to `path` succeeds.
+/// Anything other than a socket is left alone and makes the bind fail.
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path),
+        _ => Ok(()),
+    }
+}
+
+/// Registers the metrics and serves the HTTP API on `bind:port`, or on `unix_socket`, until
+/// shut down. With `tls_cert` and `tls_key` set, TCP connections are served over TLS.
+///
+/// With `grpc_port` set, the gRPC API is served alongside on its own runtime, with the same
+/// bind address and TLS settings.
+///
+/// On SIGTERM or SIGINT the server stops accepting connections and gives in-flight requests
+/// up to `shutdown_grace` to finish.
+pub fn run(config: Config) -> std::io::Result<()> {
+    register_metrics(); // Register Prometheus metrics
+    cache::init(config.cache_entries);
+
+    let addr = SocketAddr::new(config.bind, config.port);
+    #[cfg(feature = "tls")]
+    let tls = config.tls()?;
+    let listener = match &config.unix_socket {
+        #[cfg(feature = "tls")]
+        Some(_) if tls.is_some() => {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "--unix-socket cannot be combined with TLS"));
+        }
+        #[cfg(unix)]
+        Some(path) => {
+            remove_stale_socket(path)?;
+            Listener::Unix(path.clone())
+        }
+        #[cfg(not(unix))]
+        Some(_) => {
+            return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "--unix-socket is only supported on Unix"));
+        }
+        #[cfg(feature = "tls")]
+        None if tls.is_some() => Listener::Tls(addr, Box::new(tls::server_config(tls.clone().unwrap(), &[b"h2", b"http/1.1"]))),
+        None => Listener::Tcp(addr),
+    };
+    let shutdown_grace = config.shutdown_grace;
+    let jobs = web::Data::new(JobStore::new(config.job_ttl, config.lint_options.clone(), config.max_jobs, config.max_input_bytes));
+    let max_input_bytes = config.max_input_bytes;
+    let max_bulk_bytes = config.max_bulk_bytes;
+    let cors_origins = config.cors_origins.clone();
+    if config.rate_limit.is_some_and(|rate| !(rate > 0.0 && rate.is_finite())) {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "--rate-limit must be a positive number"));
+    }
+    let access = web::Data::new(auth::Access {
+        keys: config.api_keys_file.as_deref().map(auth::ApiKeys::load).transpose()?,
+        limiter: config.rate_limit.map(|rate| auth::RateLimiter::new(rate, config.rate_limit_burst)),
+    });
+    let config = web::Data::new(config);
+    #[cfg(feature = "grpc")]
+    let grpc = config
+        .grpc_port
+        .map(|grpc_port| {
+            let addr = SocketAddr::new(config.bind, grpc_port);
+            #[cfg(feature = "tls")]
+            let tls = tls.clone().map(|resolver| std::sync::Arc::new(tls::server_config(resolver, &[b"h2"])));
+            #[cfg(not(feature = "tls"))]
+            let tls = None;
+            spawn_grpc(addr, tls, config.clone().into_inner(), access.clone().into_inner())
+        })
+        .transpose()?;
+    info!("Starting lintymclintface in web service mode on {}", listener);
+    #[cfg(unix)]
+    let socket_path = match &listener {
+        Listener::Unix(path) => Some(path.clone()),
+        _ => None,
+    };
+    let server = HttpServer::new(move || {
+            App::new()
+                .app_data(config.clone())
+                .app_data(jobs.clone())
+                .app_data(access.clone())
+                .wrap(actix_web::middleware::from_fn(auth::guard))
+                // Outside authentication, so that preflight requests are answered first.
+                .wrap(cors(&cors_origins))
+                // Outermost, so that everything logged for a request carries its ID.
+                .wrap(actix_web::middleware::from_fn(request_id::middleware))
+                .service(web::scope(API_PREFIX).configure(|cfg| api(cfg, max_input_bytes, max_bulk_bytes)))
+                // Unversioned aliases from before the API was versioned.
+                .configure(|cfg| api(cfg, max_input_bytes, max_bulk_bytes))
+                .route("/metrics", web::get().to(metrics)) // Add metrics endpoint
+                .route("/healthz", web::get().to(health::healthz))
+                .route("/readyz", web::get().to(health::readyz))
+                .route("/version", web::get().to(health::version))
+                .route("/openapi.json", web::get().to(openapi::openapi_json))
+                .route("/", web::get().to(playground::index))
+        })
+        .shutdown_timeout(shutdown_grace.as_secs());
+    let server = match listener {
+        Listener::Tcp(addr) => server.bind(addr)?,
+        #[cfg(feature = "tls")]
+        Listener::Tls(addr, tls) => server.bind_rustls_0_23(addr, *tls)?,
+        #[cfg(unix)]
+        Listener::Unix(path) => server.bind_uds(path)?,
+    };
+    let result = actix_web::rt::System::new().block_on(server.run());
+    #[cfg(unix)]
+    if let Some(path) = socket_path {
+        let _ = std::fs::remove_file(path);
+    }
+    #[cfg(feature = "grpc")]
+    if let Some(grpc) = grpc {
+        grpc.stop(shutdown_grace);
+    }
+    result
+}
+
+/// The gRPC server running on its own thread.
+#[cfg(feature = "grpc")]
+struct GrpcServer {
+    shutdown: tokio::sync::oneshot::Sender<()>,
+    stopped: std::sync::mpsc::Receiver<()>,
+}
+
+#[cfg(feature = "grpc")]
+impl GrpcServer {
+    /// Stops accepting calls and waits up to `grace` for in-flight ones, such as open
+    /// `LintStream` sessions, to finish.
+    fn stop(self, grace: Duration) {
+        let _ = self.shutdown.send(());
+        let _ = self.stopped.recv_timeout(grace);
+    }
+}
+
+/// Starts the gRPC API on `addr` on a separate Tokio runtime, as tonic does not run inside
+/// actix's single-threaded workers.
+#[cfg(feature = "grpc")]
+fn spawn_grpc(
+    addr: SocketAddr,
+    tls: Option<grpc::TlsConfig>,
+    config: std::sync::Arc<Config>,
+    access: std::sync::Arc<auth::Access>,
+) -> std::io::Result<GrpcServer> {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+    let (shutdown, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let (stopped_tx, stopped) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let result = runtime.block_on(grpc::serve(addr, tls, config, access, async {
+            let _ = shutdown_rx.await;
+        }));
+        if let Err(e) = result {
+            tracing::error!("gRPC server failed: {}", e);
+        }
+        let _ = stopped_tx.send(());
+    });
+    Ok(GrpcServer { shutdown, stopped })
+}
@@ -0,0 +1,64 @@
+//! The OpenAPI 3 description of the lint API, served at `/openapi.json`.
+//!
+//! The schemas are derived from the request and response types, so the document stays in
+//! step with the handlers it describes.
+
+use actix_web::HttpResponse;
+use lintymclintface::document::Position;
+use lintymclintface::output::OutputFormat;
+use lintymclintface::{Severity, SyntaxError, SyntaxNode};
+use serde::Serialize;
+use utoipa::{OpenApi, ToSchema};
+
+use super::jobs::{JobStatus, JobView};
+use super::{ArchiveFileResult, ArchiveReport, ArchiveSummary, BatchItem, BatchItemResult, LintRequest};
+
+/// The body of an error response.
+#[derive(Serialize, ToSchema)]
+pub struct ApiError {
+    pub error: String,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "lintymclintface",
+        description = "Syntax and style checks for Java, Python and R.",
+        license(name = "MIT"),
+    ),
+    paths(
+        super::lint_service,
+        super::parse_service,
+        super::lint_batch_service,
+        super::lint_archive_service,
+        super::submit_job,
+        super::get_job,
+        super::cancel_job,
+        super::job_events,
+    ),
+    components(schemas(
+        LintRequest,
+        SyntaxError,
+        Severity,
+        SyntaxNode,
+        OutputFormat,
+        Position,
+        BatchItem,
+        BatchItemResult,
+        ArchiveReport,
+        ArchiveFileResult,
+        ArchiveSummary,
+        JobView,
+        JobStatus,
+        ApiError,
+    )),
+    tags(
+        (name = "lint", description = "Linting files synchronously"),
+        (name = "jobs", description = "Linting large inputs in the background"),
+    )
+)]
+struct ApiDoc;
+
+pub async fn openapi_json() -> HttpResponse {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
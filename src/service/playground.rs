@@ -0,0 +1,14 @@
+//! The web playground served at `/`.
+//!
+//! A single self-contained page, compiled into the binary, with an editor, a language
+//! picker, the diagnostics for the current text and its syntax tree. It calls `POST /v1/lint`
+//! and `POST /v1/parse` as the user types, so it needs nothing beyond the API itself.
+
+use actix_web::http::header::ContentType;
+use actix_web::HttpResponse;
+
+const PAGE: &str = include_str!("playground.html");
+
+pub async fn index() -> HttpResponse {
+    HttpResponse::Ok().content_type(ContentType::html()).body(PAGE)
+}
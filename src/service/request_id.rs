@@ -0,0 +1,54 @@
+//! Request IDs and the per-request tracing span.
+//!
+//! Every HTTP request is handled inside an `http_request` span carrying its request ID,
+//! so each log line written while handling it names the request. The ID is taken from the
+//! caller's `X-Request-Id` header, as set by a gateway, or generated, and is sent back in
+//! the response's `X-Request-Id` header either way.
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use tracing::Instrument;
+
+/// The header carrying the request ID.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest request ID accepted from a caller.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// The request ID `value` if it is usable as one: printable ASCII without spaces and not
+/// too long, so that it can go into logs and headers unchanged.
+pub fn accepted(value: Option<&str>) -> Option<&str> {
+    value.filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic()))
+}
+
+/// A new random request ID.
+pub fn generate() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Middleware that runs each request in an `http_request` span and echoes its request ID.
+pub async fn middleware(req: ServiceRequest, next: Next<impl MessageBody>) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let header = req.headers().get(&REQUEST_ID_HEADER).and_then(|v| v.to_str().ok());
+    let request_id = accepted(header).map_or_else(generate, str::to_string);
+    let span = tracing::info_span!(
+        "http_request",
+        request_id = %request_id,
+        http.request.method = %req.method(),
+        url.path = %req.path(),
+        http.response.status_code = tracing::field::Empty,
+    );
+    #[cfg(feature = "otel")]
+    {
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+        span.set_parent(super::telemetry::remote_context(req.headers()));
+    }
+
+    let mut response = next.call(req).instrument(span.clone()).await?;
+    span.record("http.response.status_code", response.status().as_u16());
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    Ok(response)
+}
@@ -0,0 +1,82 @@
+//! Export of `tracing` spans to an OpenTelemetry collector over OTLP/gRPC, enabled with
+//! `--otlp-endpoint`.
+//!
+//! Spans are batched and sent from a small Tokio runtime of their own, so exporting never
+//! competes with request handling. Incoming W3C `traceparent` headers are honoured, so that
+//! spans from this service join the traces started by a gateway in front of it.
+
+use actix_web::http::header::HeaderMap;
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::{Context, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing::Subscriber;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Keeps the exporter running. Dropping it flushes the spans not yet sent.
+pub struct Telemetry {
+    provider: TracerProvider,
+    runtime: Option<tokio::runtime::Runtime>,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            tracing::warn!("Failed to flush traces: {}", e);
+        }
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// Creates a layer that exports spans to the OTLP/gRPC collector at `endpoint`, such as
+/// `http://localhost:4317`. The collector is connected to lazily, so one that is down or
+/// not started yet only costs the spans exported in the meantime.
+pub fn layer<S>(endpoint: &str) -> std::io::Result<(impl Layer<S>, Telemetry)>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("otlp-export")
+        .enable_all()
+        .build()?;
+    // The exporter's channel and the batch processor spawn their tasks on the current runtime.
+    let _guard = runtime.enter();
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("--otlp-endpoint: {}", e)))?;
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_resource(Resource::new([
+            KeyValue::new("service.name", env!("CARGO_PKG_NAME")),
+            KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+        ]))
+        .build();
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer(env!("CARGO_PKG_NAME")));
+    Ok((layer, Telemetry { provider, runtime: Some(runtime) }))
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+/// The trace context a caller sent in `traceparent` and `tracestate` headers, if any.
+pub fn remote_context(headers: &HeaderMap) -> Context {
+    TraceContextPropagator::new().extract(&HeaderExtractor(headers))
+}
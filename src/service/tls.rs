@@ -0,0 +1,111 @@
+//! TLS for the HTTP service and the gRPC API, enabled with `--tls-cert` and `--tls-key`.
+//!
+//! The certificate chain and private key are read from PEM files. Both files are checked
+//! for changes at most every [`RELOAD_CHECK_INTERVAL`] while handshakes come in, so a
+//! renewed certificate is picked up without a restart. If the new files cannot be loaded,
+//! for instance because only one of them has been replaced yet, the previous certificate
+//! stays in use.
+
+use rustls::crypto::ring;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use rustls::ServerConfig;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{info, warn};
+
+/// How often the certificate and key files are checked for changes.
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Loaded {
+    key: Arc<CertifiedKey>,
+    modified: (Option<SystemTime>, Option<SystemTime>),
+    checked: Instant,
+}
+
+/// Serves the certificate in `--tls-cert`, reloading it when the files change.
+pub struct CertificateResolver {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    loaded: Mutex<Loaded>,
+}
+
+impl std::fmt::Debug for CertificateResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CertificateResolver").field("cert_path", &self.cert_path).finish_non_exhaustive()
+    }
+}
+
+fn invalid(path: &Path, msg: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), msg))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn load(cert_path: &Path, key_path: &Path) -> io::Result<CertifiedKey> {
+    let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(cert_path)?))
+        .collect::<Result<Vec<CertificateDer<'static>>, _>>()?;
+    if certs.is_empty() {
+        return Err(invalid(cert_path, "no certificates found"));
+    }
+    let key: PrivateKeyDer<'static> = rustls_pemfile::private_key(&mut BufReader::new(File::open(key_path)?))?
+        .ok_or_else(|| invalid(key_path, "no private key found"))?;
+    let signing_key = ring::sign::any_supported_type(&key).map_err(|e| invalid(key_path, e))?;
+    let certified = CertifiedKey::new(certs, signing_key);
+    certified.keys_match().map_err(|e| invalid(cert_path, e))?;
+    Ok(certified)
+}
+
+impl CertificateResolver {
+    pub fn load(cert_path: &Path, key_path: &Path) -> io::Result<Self> {
+        let modified = (modified(cert_path), modified(key_path));
+        let key = Arc::new(load(cert_path, key_path)?);
+        Ok(CertificateResolver {
+            cert_path: cert_path.to_path_buf(),
+            key_path: key_path.to_path_buf(),
+            loaded: Mutex::new(Loaded { key, modified, checked: Instant::now() }),
+        })
+    }
+
+    fn current(&self) -> Arc<CertifiedKey> {
+        let mut loaded = self.loaded.lock().unwrap();
+        if loaded.checked.elapsed() >= RELOAD_CHECK_INTERVAL {
+            loaded.checked = Instant::now();
+            let modified = (modified(&self.cert_path), modified(&self.key_path));
+            if modified != loaded.modified {
+                match load(&self.cert_path, &self.key_path) {
+                    Ok(key) => {
+                        info!("Reloaded TLS certificate from {}", self.cert_path.display());
+                        loaded.key = Arc::new(key);
+                        loaded.modified = modified;
+                    }
+                    Err(e) => warn!("Keeping the current TLS certificate: {}", e),
+                }
+            }
+        }
+        loaded.key.clone()
+    }
+}
+
+impl ResolvesServerCert for CertificateResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current())
+    }
+}
+
+/// A server configuration presenting `resolver`'s certificate and offering `alpn` protocols.
+pub fn server_config(resolver: Arc<CertificateResolver>, alpn: &[&[u8]]) -> ServerConfig {
+    let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("the ring provider supports the default protocol versions")
+        .with_no_client_auth()
+        .with_cert_resolver(resolver);
+    config.alpn_protocols = alpn.iter().map(|p| p.to_vec()).collect();
+    config
+}
@@ -0,0 +1,114 @@
+//! Prometheus metrics for command-line runs, written with `--metrics-file` in the text
+//! format read by node_exporter's textfile collector.
+//!
+//! The service exports its metrics on `/metrics` while it runs; a CLI run is over before
+//! anything could scrape it, so it leaves a file behind instead. Pointing
+//! `--collector.textfile.directory` at the file's directory lets scheduled scans feed the
+//! same dashboards as the service.
+
+use lintymclintface::{LintReport, LinterError, Severity, SUPPORTED_LANGUAGES};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// What a run linted, counted as it goes.
+pub struct RunMetrics {
+    started: Instant,
+    files: BTreeMap<&'static str, u64>,
+    diagnostics: BTreeMap<(&'static str, String, Severity), u64>,
+    failures: BTreeMap<(&'static str, &'static str), u64>,
+    /// Files that could not be read or were over the size limit, kept apart from
+    /// `failures` so that those only count files the linter gave up on.
+    input_errors: BTreeMap<&'static str, u64>,
+}
+
+/// The language as a label value: one of the supported languages or `unknown`, so that a
+/// typo on the command line cannot add series.
+fn language_label(language: &str) -> &'static str {
+    SUPPORTED_LANGUAGES.iter().find(|l| **l == language).copied().unwrap_or("unknown")
+}
+
+/// Escapes a label value for the text format.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+impl RunMetrics {
+    pub fn new() -> Self {
+        RunMetrics { started: Instant::now(), files: BTreeMap::new(), diagnostics: BTreeMap::new(), failures: BTreeMap::new(), input_errors: BTreeMap::new() }
+    }
+
+    /// Counts the outcome of linting one file as `language`.
+    pub fn record(&mut self, language: &str, result: &Result<LintReport, LinterError>) {
+        let language = language_label(language);
+        *self.files.entry(language).or_default() += 1;
+        match result {
+            Ok(report) => {
+                for error in &report.errors {
+                    *self.diagnostics.entry((language, error.rule.clone(), error.severity)).or_default() += 1;
+                }
+            }
+            Err(LinterError::Io(_)) => *self.input_errors.entry(language).or_default() += 1,
+            Err(e) => {
+                let reason = match e {
+                    LinterError::Timeout(_) => "timeout",
+                    LinterError::UnsupportedLanguage(_) => "unsupported_language",
+                    _ => "error",
+                };
+                *self.failures.entry((language, reason)).or_default() += 1;
+            }
+        }
+    }
+
+    /// Renders the metrics, taking the run to have finished now.
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# HELP lint_files_total Files linted by the run, by language.");
+        let _ = writeln!(out, "# TYPE lint_files_total counter");
+        for (language, count) in &self.files {
+            let _ = writeln!(out, "lint_files_total{{language=\"{}\"}} {}", language, count);
+        }
+        let _ = writeln!(out, "# HELP lint_diagnostics_total Diagnostics reported by the run, by language, rule and severity.");
+        let _ = writeln!(out, "# TYPE lint_diagnostics_total counter");
+        for ((language, rule, severity), count) in &self.diagnostics {
+            let _ = writeln!(
+                out,
+                "lint_diagnostics_total{{language=\"{}\",rule=\"{}\",severity=\"{}\"}} {}",
+                language,
+                escape(rule),
+                severity,
+                count
+            );
+        }
+        let _ = writeln!(out, "# HELP lint_parse_failures_total Files the run could not lint, by language and reason.");
+        let _ = writeln!(out, "# TYPE lint_parse_failures_total counter");
+        for ((language, reason), count) in &self.failures {
+            let _ = writeln!(out, "lint_parse_failures_total{{language=\"{}\",reason=\"{}\"}} {}", language, reason, count);
+        }
+        let _ = writeln!(out, "# HELP lint_input_errors_total Files the run could not read or that exceeded the size limit, by language.");
+        let _ = writeln!(out, "# TYPE lint_input_errors_total counter");
+        for (language, count) in &self.input_errors {
+            let _ = writeln!(out, "lint_input_errors_total{{language=\"{}\"}} {}", language, count);
+        }
+        let _ = writeln!(out, "# HELP lint_run_duration_seconds How long the run took.");
+        let _ = writeln!(out, "# TYPE lint_run_duration_seconds gauge");
+        let _ = writeln!(out, "lint_run_duration_seconds {}", self.started.elapsed().as_secs_f64());
+        let _ = writeln!(out, "# HELP lint_run_last_completion_timestamp_seconds When the run finished, in seconds since the Unix epoch.");
+        let _ = writeln!(out, "# TYPE lint_run_last_completion_timestamp_seconds gauge");
+        let finished = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let _ = writeln!(out, "lint_run_last_completion_timestamp_seconds {}", finished.as_secs());
+        out
+    }
+
+    /// Writes the metrics to `path`, replacing the file atomically so that the collector
+    /// never reads a partly written one.
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        let contents = self.render();
+        let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        // Without the .prom extension, the collector skips the temporary file.
+        let tmp = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+        std::fs::write(&tmp, contents)?;
+        std::fs::rename(&tmp, path)
+    }
+}
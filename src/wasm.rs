@@ -0,0 +1,38 @@
+//! Browser bindings, built with
+//! `wasm-pack build --target web -- --no-default-features --features wasm`.
+//!
+//! From JavaScript:
+//!
+//! ```js
+//! import init, { lint } from "./pkg/lintymclintface.js";
+//! await init();
+//! const diagnostics = lint("java", source); // [{ line, column, message, severity, rule }]
+//! ```
+
+use crate::LinterError;
+use wasm_bindgen::prelude::*;
+
+/// Lints `code` written in `language` (java, python or r).
+///
+/// Returns an array of `{ line, column, message, severity, rule }` objects and throws
+/// an `Error` if the language is unsupported or the code cannot be parsed.
+#[wasm_bindgen]
+pub fn lint(language: &str, code: &str) -> Result<JsValue, JsError> {
+    let errors = crate::lint(language, code).map_err(|e| match e {
+        LinterError::UnsupportedLanguage(msg) => JsError::new(&format!("Unsupported language: {}", msg)),
+        LinterError::Io(msg)
+        | LinterError::Parse(msg)
+        | LinterError::TreeSitterParseError(msg)
+        | LinterError::Timeout(msg)
+        | LinterError::InvalidOption(msg) => {
+            JsError::new(&msg)
+        }
+    })?;
+    Ok(serde_wasm_bindgen::to_value(&errors)?)
+}
+
+/// Returns the languages accepted by [`lint`].
+#[wasm_bindgen(js_name = supportedLanguages)]
+pub fn supported_languages() -> Vec<String> {
+    crate::SUPPORTED_LANGUAGES.iter().map(|l| l.to_string()).collect()
+}
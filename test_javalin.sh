@@ -6,13 +6,6 @@ if [ ! -f "$LINTER_CMD" ]; then
     echo "Please build the project first by running 'cargo build --release'"
     exit 1
 fi
-GREEN='\033[0;32m'
-RED='\033[0;31m'
-NC='\033[0m' # No Color
-
-passed_files=0
-failed_files=0
-total_files=0
 
 echo "Running linter on javalin project..."
 REPO_DIR="tmp_javalin_repo"
@@ -29,28 +22,13 @@ else
   echo "Repository ${REPO_DIR} already exists. Skipping clone."
 fi
 
-for file in $(find ${REPO_DIR} -name "*.java"); do
-    ((total_files++))
-    output=$($LINTER_CMD -l java -f "$file")
-    if [ -z "$output" ]; then
-        echo -e "${GREEN}PASS${NC}: $file"
-        ((passed_files++))
-    else
-        echo -e "${RED}FAIL${NC}: $file"
-        echo "Expected no errors, but got:"
-        echo "$output"
-        ((failed_files++))
-    fi
-done
-
-echo ""
-echo "-----------------"
-echo "javalin test summary:"
-echo "Total files: $total_files"
-echo -e "${GREEN}Passed: $passed_files${NC}"
-echo -e "${RED}Failed: $failed_files${NC}"
-echo "-----------------"
-
-if [ "$failed_files" -ne 0 ]; then
-    exit 1
+# Files listed in the manifest, if there is one, may fail without failing the run.
+KNOWN_FAILURES="corpus/javalin.known-failures"
+KNOWN_FAILURES_ARGS=()
+if [ -f "${KNOWN_FAILURES}" ]; then
+  KNOWN_FAILURES_ARGS=(--known-failures "${KNOWN_FAILURES}")
 fi
+
+# Each run is compared with the previous one, whose summary it then replaces.
+$LINTER_CMD corpus run "${REPO_DIR}" --language java --expect clean "${KNOWN_FAILURES_ARGS[@]}" \
+    --previous "${REPO_DIR}.json" --json "${REPO_DIR}.json" --html "${REPO_DIR}.html"
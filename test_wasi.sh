@@ -0,0 +1,70 @@
+#!/bin/bash
+
+# Builds the CLI for wasm32-wasip1 and runs it under wasmtime against tests/working and tests/failing.
+# Requires: rustup target add wasm32-wasip1, wasmtime, and a wasm-capable C compiler for the
+# tree-sitter grammars (e.g. wasi-sdk: export CC_wasm32_wasip1=/opt/wasi-sdk/bin/clang).
+
+if ! command -v wasmtime &> /dev/null
+then
+    echo "wasmtime could not be found. Please install it from https://wasmtime.dev to run the tests."
+    exit 1
+fi
+
+cargo build --release --target wasm32-wasip1 --no-default-features || exit 1
+
+LINTER_WASM="./target/wasm32-wasip1/release/lintymclintface.wasm"
+LINTER_CMD="wasmtime run --dir=. $LINTER_WASM"
+
+GREEN='\033[0;32m'
+RED='\033[0;31m'
+NC='\033[0m' # No Color
+
+passed_tests=0
+failed_tests=0
+
+language_for() {
+    case "$1" in
+        *.java) echo "java" ;;
+        *.py) echo "python" ;;
+        *.R) echo "r" ;;
+    esac
+}
+
+echo "Running tests for working files..."
+for file in tests/working/*; do
+    output=$($LINTER_CMD -l "$(language_for "$file")" -f "$file")
+    if [ -z "$output" ]; then
+        echo -e "${GREEN}PASS${NC}: $file"
+        ((passed_tests++))
+    else
+        echo -e "${RED}FAIL${NC}: $file"
+        echo "Expected no errors, but got:"
+        echo "$output"
+        ((failed_tests++))
+    fi
+done
+
+echo ""
+echo "Running tests for failing files..."
+for file in tests/failing/*; do
+    output=$($LINTER_CMD -l "$(language_for "$file")" -f "$file")
+    if [ -n "$output" ]; then
+        echo -e "${GREEN}PASS${NC}: $file"
+        ((passed_tests++))
+    else
+        echo -e "${RED}FAIL${NC}: $file"
+        echo "Expected errors, but got none."
+        ((failed_tests++))
+    fi
+done
+
+echo ""
+echo "-----------------"
+echo "WASI test summary:"
+echo -e "${GREEN}Passed: $passed_tests${NC}"
+echo -e "${RED}Failed: $failed_tests${NC}"
+echo "-----------------"
+
+if [ "$failed_tests" -ne 0 ]; then
+    exit 1
+fi
@@ -0,0 +1,36 @@
+use lintymclintface::cache::{CacheKey, DiskCache};
+use lintymclintface::{LintOptions, LintReport};
+
+#[test]
+fn test_cache_key_covers_language_options_and_code() {
+    let options = LintOptions::default();
+    let key = CacheKey::new("python", "x = 1\n", &options);
+    assert_eq!(key, CacheKey::new("python", "x = 1\n", &options));
+    assert_eq!(key.as_str().len(), 64);
+
+    assert_ne!(key, CacheKey::new("r", "x = 1\n", &options));
+    assert_ne!(key, CacheKey::new("python", "x = 2\n", &options));
+    let capped = LintOptions { max_diagnostics: Some(1), ..LintOptions::default() };
+    assert_ne!(key, CacheKey::new("python", "x = 1\n", &capped));
+    let selected = LintOptions { rules: Some(vec!["syntax-error".to_string()]), ..LintOptions::default() };
+    assert_ne!(key, CacheKey::new("python", "x = 1\n", &selected));
+    let targeted = LintOptions { target_version: Some("2.7".parse().unwrap()), ..LintOptions::default() };
+    assert_ne!(key, CacheKey::new("python", "x = 1\n", &targeted));
+}
+
+#[test]
+fn test_disk_cache_round_trips_reports() {
+    let dir = std::env::temp_dir().join(format!("lint-disk-cache-{}", std::process::id()));
+    let cache = DiskCache::open(&dir).unwrap();
+    let options = LintOptions::default();
+    let key = CacheKey::new("r", "x <- 1\n", &options);
+    assert!(cache.get(&key).is_none());
+
+    let report = lintymclintface::lint_with_options("r", "x <- 1\n", &options).unwrap();
+    cache.put(&key, &report).unwrap();
+    let cached: LintReport = cache.get(&key).unwrap();
+    assert_eq!(cached.errors.len(), 1);
+    assert_eq!(cached.errors[0].rule, "r/arrow-assignment");
+    assert!(!cached.truncated);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
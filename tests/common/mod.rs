@@ -0,0 +1,141 @@
+//! The harness shared by the tests that run `lintymclintface --service`.
+
+// Each test crate uses only some of these helpers.
+#![allow(dead_code)]
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+pub fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+/// A `lintymclintface --service --port <port>` command with its output discarded.
+pub fn service_command(port: u16) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_lintymclintface"));
+    command
+        .arg("--service")
+        .arg("--port")
+        .arg(port.to_string())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    command
+}
+
+/// A `lintymclintface --service` process listening on a free local port, killed on drop.
+pub struct Service {
+    pub child: Child,
+    pub port: u16,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.body).unwrap_or_else(|_| panic!("Response is not JSON: {}", self.body))
+    }
+}
+
+impl Service {
+    pub fn start(extra_args: &[&str]) -> Self {
+        let port = free_port();
+        let mut command = service_command(port);
+        command.args(extra_args);
+        Self::launch(&mut command, port, port)
+    }
+
+    /// Starts the service with `RUST_LOG` set to `filter`, writing its log to `log`.
+    pub fn start_logging(extra_args: &[&str], filter: &str, log: &std::fs::File) -> Self {
+        let port = free_port();
+        let mut command = service_command(port);
+        command.args(extra_args).env("RUST_LOG", filter).stderr(log.try_clone().unwrap());
+        Self::launch(&mut command, port, port)
+    }
+
+    /// Spawns a [`service_command`] for `port` and waits until `ready_port` accepts connections.
+    pub fn launch(command: &mut Command, port: u16, ready_port: u16) -> Self {
+        let child = command.spawn().expect("Failed to start linter service");
+        let service = Service { child, port };
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while TcpStream::connect(("127.0.0.1", ready_port)).is_err() {
+            assert!(Instant::now() < deadline, "Service did not start listening on port {}", ready_port);
+            sleep(Duration::from_millis(50));
+        }
+        service
+    }
+
+    pub fn request(&self, method: &str, path: &str, body: Option<&str>) -> Response {
+        self.request_bytes(method, path, "application/json", body.unwrap_or("").as_bytes())
+    }
+
+    pub fn request_bytes(&self, method: &str, path: &str, content_type: &str, body: &[u8]) -> Response {
+        self.send(method, path, &[("Content-Type", content_type)], body)
+    }
+
+    pub fn send(&self, method: &str, path: &str, headers: &[(&str, &str)], body: &[u8]) -> Response {
+        let mut stream = TcpStream::connect(("127.0.0.1", self.port)).expect("Failed to connect to service");
+        let mut head = format!("{} {} HTTP/1.1\r\nHost: localhost\r\n", method, path);
+        for (name, value) in headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n", body.len()));
+        stream.write_all(head.as_bytes()).unwrap();
+        stream.write_all(body).unwrap();
+
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).unwrap();
+        let raw = String::from_utf8_lossy(&raw).to_string();
+        let (head, body) = raw.split_once("\r\n\r\n").expect("Malformed HTTP response");
+        let mut lines = head.lines();
+        let status = lines.next().unwrap().split_whitespace().nth(1).unwrap().parse().unwrap();
+        let headers: Vec<(String, String)> = lines
+            .filter_map(|l| l.split_once(':'))
+            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+            .collect();
+        let chunked = headers
+            .iter()
+            .any(|(k, v)| k.eq_ignore_ascii_case("transfer-encoding") && v.eq_ignore_ascii_case("chunked"));
+        let body = if chunked { dechunk(body) } else { body.to_string() };
+        Response { status, headers, body }
+    }
+
+    pub fn post_json(&self, path: &str, body: &Value) -> Response {
+        self.request("POST", path, Some(&body.to_string()))
+    }
+}
+
+impl Drop for Service {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn dechunk(mut body: &str) -> String {
+    let mut out = String::new();
+    while let Some((size, rest)) = body.split_once("\r\n") {
+        let size = usize::from_str_radix(size.trim(), 16).unwrap_or(0);
+        if size == 0 {
+            break;
+        }
+        out.push_str(&rest[..size]);
+        body = &rest[size + 2..];
+    }
+    out
+}
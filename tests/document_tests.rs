@@ -0,0 +1,52 @@
+use lintymclintface::document::{Document, Position, TextEdit};
+
+fn edit(start: (usize, usize), end: (usize, usize), text: &str) -> TextEdit {
+    TextEdit {
+        start: Position { line: start.0, column: start.1 },
+        end: Position { line: end.0, column: end.1 },
+        text: text.to_string(),
+    }
+}
+
+fn summary(errors: &[lintymclintface::SyntaxError]) -> Vec<(usize, usize, String)> {
+    errors.iter().map(|e| (e.line, e.column, e.message.clone())).collect()
+}
+
+#[test]
+fn test_incremental_edits_match_full_lint() {
+    let mut document = Document::open("java", "public class Test {\n    int x = 1;\n}\n".to_string()).unwrap();
+    assert!(document.diagnostics().unwrap().is_empty());
+
+    // Delete the semicolon.
+    document.apply(&edit((2, 14), (2, 15), "")).unwrap();
+    assert_eq!(document.text(), "public class Test {\n    int x = 1\n}\n");
+    let errors = document.diagnostics().unwrap();
+    assert_eq!(summary(&errors), summary(&lintymclintface::lint("java", document.text()).unwrap()));
+    assert_eq!(errors[0].message, "Missing ;");
+
+    // Put it back and add a second field on a new line.
+    document.apply(&edit((2, 14), (2, 14), ";\n    int y = 2;")).unwrap();
+    assert_eq!(document.text(), "public class Test {\n    int x = 1;\n    int y = 2;\n}\n");
+    assert!(document.diagnostics().unwrap().is_empty());
+}
+
+#[test]
+fn test_edits_across_lines_and_multibyte_text() {
+    let mut document = Document::open("python", "s = \"héllo\"\nx = 1\n".to_string()).unwrap();
+    // Columns count bytes: "é" is two bytes, so the closing quote is at column 12.
+    document.apply(&edit((1, 12), (2, 6), "\"\nprint(x)")).unwrap();
+    assert_eq!(document.text(), "s = \"héllo\"\nprint(x)\n");
+    let errors = document.diagnostics().unwrap();
+    assert_eq!(summary(&errors), summary(&lintymclintface::lint("python", document.text()).unwrap()));
+    assert_eq!(errors[0].rule, "python/print-statement");
+}
+
+#[test]
+fn test_invalid_edits_are_rejected() {
+    let mut document = Document::open("r", "x = 1\n".to_string()).unwrap();
+    assert!(document.apply(&edit((3, 1), (3, 1), "y")).is_err());
+    assert!(document.apply(&edit((1, 9), (1, 9), "y")).is_err());
+    assert!(document.apply(&edit((1, 4), (1, 2), "y")).is_err());
+    assert_eq!(document.text(), "x = 1\n");
+    assert!(Document::open("cobol", String::new()).is_err());
+}
@@ -3,3 +3,9 @@ Public class IncorrectKeywordCase {
     public Static void main(String[] args) {
     }
 }
+// expect: java/syntax-error@2:1 "Syntax error near 'Public class IncorrectKeywordCase'"
+// expect: java/keyword-identifier@2:8 "'class' is a keyword and cannot be used as an identifier"
+// expect: java/keyword-identifier@3:19 "'void' is a keyword and cannot be used as an identifier"
+// expect: java/missing-token@3:23 "Missing ;"
+// expect: java/syntax-error@3:29 "Syntax error near 'String[]'"
+// expect: java/missing-token@3:43 "Missing ;"
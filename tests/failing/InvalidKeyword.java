@@ -3,3 +3,4 @@ public class InvalidKeyword {
         int final = 10; // 'final' is a keyword
     }
 }
+// expect: java/keyword-identifier@3:13 "'final' is a keyword and cannot be used as an identifier"
@@ -3,3 +3,4 @@ public class InvalidKeyword2 {
         int final;
     }
 }
+// expect: java/keyword-identifier@3:13 "'final' is a keyword and cannot be used as an identifier"
@@ -3,3 +3,4 @@ public class InvalidMethodDeclaration {
         System.out.println("This is a method.");
     }
 }
+// expect: java/invalid-constructor@2:5 "Invalid constructor name 'myMethod'. Constructor name must match the class name 'InvalidMethodDeclaration'"
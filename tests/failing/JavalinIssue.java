@@ -24,3 +24,5 @@ public class JavalinIssue {
         return null; // Return null for simplicity in this test case
     }
 }
+// expect: java/syntax-error@21:127 "Syntax error near ', @NotNull RouteRole @NotNull ... roles'"
+// expect: java/syntax-error@21:158 "Syntax error near '..'"
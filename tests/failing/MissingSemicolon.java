@@ -3,3 +3,4 @@ public class MissingSemicolon {
         System.out.println("Hello, World!")
     }
 }
+// expect: java/missing-token@3:44 "Missing ;"
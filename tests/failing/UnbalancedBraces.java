@@ -3,3 +3,4 @@ public class UnbalancedBraces {
         System.out.println("Hello, World!");
     }
 // Missing closing brace
+// expect: java/missing-token@5:25 "Missing }"
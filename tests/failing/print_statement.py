@@ -1 +1,2 @@
-print("Hello, world!")
\ No newline at end of file
+print("Hello, world!")
+# expect: python/print-statement@1:1 "Use of print statements is discouraged"
@@ -1,2 +1,3 @@
 def my_func
     pass
+# expect: python/syntax-error@1:1 "Syntax error near 'def my_func\n    pass'"
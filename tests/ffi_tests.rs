@@ -0,0 +1,78 @@
+use lintymclintface::ffi::*;
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+fn lint(language: &str, code: &str) -> *mut LmcfDiagnosticList {
+    let language = CString::new(language).unwrap();
+    unsafe { lmcf_lint(language.as_ptr(), code.as_ptr() as *const _, code.len()) }
+}
+
+#[test]
+fn test_lmcf_lint_reports_diagnostics() {
+    let list = lint("java", "public class Test {\n    int x = 1\n}\n");
+    unsafe {
+        assert!((*list).error.is_null());
+        assert_eq!((*list).len, 1);
+        let diagnostic = &*(*list).diagnostics;
+        assert_eq!((diagnostic.line, diagnostic.column), (2, 14));
+        assert_eq!(diagnostic.severity, LmcfSeverity::LmcfError);
+        assert_eq!(CStr::from_ptr(diagnostic.message).to_str().unwrap(), "Missing ;");
+        assert_eq!(CStr::from_ptr(diagnostic.rule).to_str().unwrap(), "java/missing-token");
+        lmcf_diagnostics_free(list);
+    }
+}
+
+#[test]
+fn test_lmcf_lint_clean_code() {
+    // The code is not NUL-terminated; only `len` bytes may be read.
+    let code = "x = 1\ngarbage that must not be read";
+    let list = unsafe { lmcf_lint(c"python".as_ptr(), code.as_ptr() as *const _, 6) };
+    unsafe {
+        assert!((*list).error.is_null());
+        assert_eq!((*list).len, 0);
+        assert!((*list).diagnostics.is_null());
+        lmcf_diagnostics_free(list);
+    }
+}
+
+#[test]
+fn test_lmcf_lint_reports_errors() {
+    let list = lint("cobol", "");
+    unsafe {
+        assert_eq!((*list).len, 0);
+        assert!(CStr::from_ptr((*list).error).to_str().unwrap().contains("cobol"));
+        lmcf_diagnostics_free(list);
+
+        let list = lmcf_lint(ptr::null(), ptr::null(), 0);
+        assert!(!(*list).error.is_null());
+        lmcf_diagnostics_free(list);
+
+        let invalid = [0xffu8, 0xfe];
+        let list = lmcf_lint(c"r".as_ptr(), invalid.as_ptr() as *const _, invalid.len());
+        assert!(CStr::from_ptr((*list).error).to_str().unwrap().contains("UTF-8"));
+        lmcf_diagnostics_free(list);
+    }
+}
+
+#[test]
+fn test_lmcf_lint_json() {
+    let code = "my_var <- 1\n";
+    unsafe {
+        let json = lmcf_lint_json(c"r".as_ptr(), code.as_ptr() as *const _, code.len());
+        let value: serde_json::Value = serde_json::from_str(CStr::from_ptr(json).to_str().unwrap()).unwrap();
+        assert_eq!(value[0]["rule"], "r/arrow-assignment");
+        assert_eq!(value[0]["severity"], "warning");
+        lmcf_string_free(json);
+
+        let json = lmcf_lint_json(c"cobol".as_ptr(), ptr::null(), 0);
+        let value: serde_json::Value = serde_json::from_str(CStr::from_ptr(json).to_str().unwrap()).unwrap();
+        assert!(value["error"].as_str().unwrap().contains("cobol"));
+        lmcf_string_free(json);
+    }
+}
+
+#[test]
+fn test_lmcf_version() {
+    let version = unsafe { CStr::from_ptr(lmcf_version()) };
+    assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+}
@@ -0,0 +1,200 @@
+//! Checks the fixtures in `tests/failing` and `tests/working` against the diagnostics they
+//! declare, in-process through the library.
+//!
+//! Each expected diagnostic is a comment on a line of its own after the code, in the comment
+//! syntax of the fixture's language:
+//!
+//! ```text
+//! // expect: java/missing-token@3:44 "Missing ;"
+//! # expect: python/print-statement@1:1 "Use of print statements is discouraged"
+//! ```
+//!
+//! giving the rule, the line and column, and the message as a JSON string. The declarations
+//! are removed before the fixture is linted, so they cannot change what is reported. A
+//! fixture that other tests lint as it is, such as `failing/syntax_error_r.R`, keeps its
+//! declarations in a sidecar file named after it with `.expect` appended instead. A fixture
+//! must produce exactly the diagnostics it declares, so one without any must lint clean.
+//!
+//! Run `cargo test --test fixtures -- --bless` to rewrite the declarations from what the
+//! linters currently report, after checking that the change is intended.
+//!
+//! Any other argument that does not start with `-` only runs the fixtures whose path
+//! contains it.
+
+use lintymclintface::{LintOptions, SyntaxError};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const FIXTURE_DIRS: &[&str] = &["failing", "working"];
+
+/// A diagnostic as fixtures declare it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Expectation {
+    line: usize,
+    column: usize,
+    rule: String,
+    message: String,
+}
+
+impl From<SyntaxError> for Expectation {
+    fn from(error: SyntaxError) -> Self {
+        Expectation { line: error.line, column: error.column, rule: error.rule, message: error.message }
+    }
+}
+
+impl Expectation {
+    fn render(&self, comment: &str) -> String {
+        let message = serde_json::to_string(&self.message).unwrap();
+        format!("{} expect: {}@{}:{} {}", comment, self.rule, self.line, self.column, message)
+    }
+}
+
+/// The line-comment marker of `language`.
+fn comment_marker(language: &str) -> &'static str {
+    match language {
+        "java" => "//",
+        _ => "#",
+    }
+}
+
+/// The text of the declaration on `line`, if it holds one.
+fn declaration<'a>(line: &'a str, comment: &str) -> Option<&'a str> {
+    line.trim().strip_prefix(comment)?.trim_start().strip_prefix("expect:").map(str::trim)
+}
+
+fn parse_declaration(text: &str) -> Result<Expectation, String> {
+    let malformed = || format!("malformed declaration '{}', expected 'RULE@LINE:COLUMN \"MESSAGE\"'", text);
+    let (rule, rest) = text.split_once('@').ok_or_else(malformed)?;
+    let (position, message) = rest.split_once(' ').ok_or_else(malformed)?;
+    let (line, column) = position.split_once(':').ok_or_else(malformed)?;
+    Ok(Expectation {
+        line: line.parse().map_err(|_| malformed())?,
+        column: column.parse().map_err(|_| malformed())?,
+        rule: rule.to_string(),
+        message: serde_json::from_str(message.trim()).map_err(|_| malformed())?,
+    })
+}
+
+/// The diagnostics `code` declares, sorted, and the code without them. Declarations must come
+/// after the code, so that removing them does not move it.
+fn split_declarations(code: &str, comment: &str) -> Result<(Vec<Expectation>, String), String> {
+    let mut expected = Vec::new();
+    let mut rest = String::new();
+    for (number, line) in code.split_inclusive('\n').enumerate() {
+        if let Some(text) = declaration(line, comment) {
+            expected.push(parse_declaration(text).map_err(|e| format!("line {}: {}", number + 1, e))?);
+        } else if expected.is_empty() {
+            rest.push_str(line);
+        } else if !line.trim().is_empty() {
+            return Err(format!("line {}: code after a declaration; declarations go at the end", number + 1));
+        }
+    }
+    expected.sort();
+    Ok((expected, rest))
+}
+
+/// The diagnostics the linter for `language` reports for `code`, sorted.
+fn reported(language: &str, code: &str) -> Result<Vec<Expectation>, String> {
+    let report = lintymclintface::lint_with_options(language, code, &LintOptions::default()).map_err(|e| format!("{:?}", e))?;
+    let mut actual: Vec<Expectation> = report.errors.into_iter().map(Expectation::from).collect();
+    actual.sort();
+    Ok(actual)
+}
+
+/// `expected` as declaration lines.
+fn render_all(expected: &[Expectation], comment: &str) -> String {
+    expected.iter().map(|expectation| expectation.render(comment) + "\n").collect()
+}
+
+/// The sidecar file that holds the declarations of the fixture at `path`.
+fn sidecar(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap().to_os_string();
+    name.push(".expect");
+    path.with_file_name(name)
+}
+
+/// Checks one fixture, or blesses it, returning what is wrong with it.
+fn check(path: &Path, language: &str, blessing: bool) -> Result<(), String> {
+    let comment = comment_marker(language);
+    let read = |path: &Path| fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e));
+    let sidecar = Some(sidecar(path)).filter(|sidecar| sidecar.exists());
+    let (expected, code) = match &sidecar {
+        Some(sidecar) => {
+            let (expected, _) = split_declarations(&read(sidecar)?, comment)?;
+            (expected, read(path)?)
+        }
+        None => split_declarations(&read(path)?, comment)?,
+    };
+    let actual = reported(language, &code)?;
+
+    if blessing {
+        if actual != expected {
+            let blessed = match &sidecar {
+                Some(sidecar) => (sidecar.as_path(), render_all(&actual, comment)),
+                None if actual.is_empty() => (path, code),
+                None if code.is_empty() || code.ends_with('\n') => (path, code + &render_all(&actual, comment)),
+                None => (path, code + "\n" + &render_all(&actual, comment)),
+            };
+            fs::write(blessed.0, blessed.1).map_err(|e| e.to_string())?;
+        }
+        return Ok(());
+    }
+
+    if expected == actual {
+        return Ok(());
+    }
+    let mut problems = String::new();
+    for missing in expected.iter().filter(|e| !actual.contains(e)) {
+        problems.push_str(&format!("\n    expected but not reported: {}", missing.render(comment)));
+    }
+    for unexpected in actual.iter().filter(|a| !expected.contains(a)) {
+        problems.push_str(&format!("\n    reported but not expected: {}", unexpected.render(comment)));
+    }
+    if problems.is_empty() {
+        problems = format!("\n    expected {} diagnostics, got {}", expected.len(), actual.len());
+    }
+    Err(problems)
+}
+
+fn fixtures() -> Vec<(PathBuf, &'static str)> {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut fixtures: Vec<_> = FIXTURE_DIRS
+        .iter()
+        .flat_map(|dir| fs::read_dir(root.join(dir)).unwrap_or_else(|e| panic!("Failed to read tests/{}: {}", dir, e)))
+        .map(|entry| entry.expect("Failed to read directory entry").path())
+        .filter_map(|path| lintymclintface::detect_language(&path).map(|language| (path, language)))
+        .collect();
+    fixtures.sort();
+    fixtures
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let blessing = args.iter().any(|arg| arg == "--bless");
+    let filters: Vec<&String> = args.iter().filter(|arg| !arg.starts_with('-')).collect();
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let selected: Vec<_> = fixtures()
+        .into_iter()
+        .filter(|(path, _)| filters.is_empty() || filters.iter().any(|f| path.to_string_lossy().contains(f.as_str())))
+        .collect();
+    println!("\nrunning {} fixtures{}", selected.len(), if blessing { " (blessing)" } else { "" });
+    let mut failed = 0;
+    for (path, language) in &selected {
+        let name = path.strip_prefix(&root).unwrap_or(path).display();
+        match check(path, language, blessing) {
+            Ok(()) => println!("fixture {} ... ok", name),
+            Err(problems) => {
+                failed += 1;
+                println!("fixture {} ... FAILED{}", name, problems);
+            }
+        }
+    }
+    println!("\nfixture result: {}. {} passed; {} failed\n", if failed == 0 { "ok" } else { "FAILED" }, selected.len() - failed, failed);
+    if failed > 0 {
+        println!("Run `cargo test --test fixtures -- --bless` to accept the reported diagnostics.\n");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
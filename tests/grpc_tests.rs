@@ -0,0 +1,188 @@
+#![cfg(feature = "grpc")]
+
+mod common;
+
+use common::{free_port, service_command, Service};
+
+mod proto {
+    tonic::include_proto!("lintymclintface.v1");
+}
+
+use proto::linter_client::LinterClient;
+use proto::{
+    lint_stream_request, lint_stream_response, BatchFile, EditDocument, LintBatchRequest, LintRequest, LintStreamRequest,
+    OpenDocument, Position, Severity, TextEdit,
+};
+
+/// A `lintymclintface --service --grpc-port` process, killed on drop.
+struct GrpcService {
+    _service: Service,
+    grpc_port: u16,
+}
+
+impl GrpcService {
+    fn start(extra_args: &[&str]) -> Self {
+        let (port, grpc_port) = (free_port(), free_port());
+        let mut command = service_command(port);
+        command.args(["--grpc-port", &grpc_port.to_string()]).args(extra_args);
+        GrpcService { _service: Service::launch(&mut command, port, grpc_port), grpc_port }
+    }
+
+    fn client(&self, runtime: &tokio::runtime::Runtime) -> LinterClient<tonic::transport::Channel> {
+        runtime
+            .block_on(LinterClient::connect(format!("http://127.0.0.1:{}", self.grpc_port)))
+            .expect("Failed to connect to gRPC server")
+    }
+}
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap()
+}
+
+#[test]
+fn test_grpc_lint_and_batch() {
+    let service = GrpcService::start(&[]);
+    let runtime = runtime();
+    let mut client = service.client(&runtime);
+
+    let response = runtime
+        .block_on(client.lint(LintRequest {
+            language: "java".to_string(),
+            code: "public class Test {\n    int x = 1\n}\n".to_string(),
+            ..Default::default()
+        }))
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.diagnostics[0].line, 2);
+    assert_eq!(response.diagnostics[0].rule, "java/missing-token");
+    assert_eq!(response.diagnostics[0].severity(), Severity::Error);
+
+    let status = runtime
+        .block_on(client.lint(LintRequest { language: "cobol".to_string(), code: String::new(), ..Default::default() }))
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+    let request = LintRequest {
+        code: "print(1)\nx = (\n".to_string(),
+        filename: Some("app.py".to_string()),
+        min_severity: Severity::Error.into(),
+        disable: vec!["missing-token".to_string()],
+        ..Default::default()
+    };
+    let response = runtime.block_on(client.lint(request)).unwrap().into_inner();
+    assert!(!response.diagnostics.is_empty());
+    assert!(response.diagnostics.iter().all(|d| d.rule == "python/syntax-error"));
+
+    let files = vec![
+        BatchFile { path: "a.py".to_string(), language: None, code: "x = 1\n".to_string() },
+        BatchFile { path: "b.txt".to_string(), language: Some("r".to_string()), code: "x <- 1\n".to_string() },
+        BatchFile { path: "README.md".to_string(), language: None, code: "# hi\n".to_string() },
+    ];
+    let results = runtime.block_on(client.lint_batch(LintBatchRequest { files })).unwrap().into_inner().results;
+    let paths: Vec<&str> = results.iter().map(|r| r.path.as_str()).collect();
+    assert_eq!(paths, ["a.py", "b.txt", "README.md"]);
+    assert!(results[0].diagnostics.is_empty());
+    assert_eq!(results[1].diagnostics[0].rule, "r/arrow-assignment");
+    assert!(results[2].error.as_deref().unwrap().contains("Cannot detect the language"));
+}
+
+#[test]
+fn test_grpc_lint_stream_session() {
+    let service = GrpcService::start(&[]);
+    let runtime = runtime();
+    let mut client = service.client(&runtime);
+
+    let position = |line, column| Some(Position { line, column });
+    let requests = vec![
+        LintStreamRequest {
+            message: Some(lint_stream_request::Message::Open(OpenDocument {
+                language: "java".to_string(),
+                text: "class A {\n    int x = 1;\n}\n".to_string(),
+            })),
+        },
+        LintStreamRequest {
+            message: Some(lint_stream_request::Message::Edit(EditDocument {
+                edits: vec![TextEdit { start: position(2, 14), end: position(2, 15), text: String::new() }],
+            })),
+        },
+        LintStreamRequest {
+            message: Some(lint_stream_request::Message::Edit(EditDocument {
+                edits: vec![TextEdit { start: position(9, 1), end: position(9, 1), text: "x".to_string() }],
+            })),
+        },
+    ];
+
+    let replies = runtime.block_on(async {
+        let mut inbound = client.lint_stream(futures_util::stream::iter(requests)).await.unwrap().into_inner();
+        let mut replies = Vec::new();
+        while let Some(reply) = inbound.message().await.unwrap() {
+            replies.push(reply);
+        }
+        replies
+    });
+
+    assert_eq!(replies.len(), 3);
+    assert_eq!(replies[0].version, 1);
+    match &replies[0].result {
+        Some(lint_stream_response::Result::Diagnostics(d)) => assert!(d.diagnostics.is_empty()),
+        other => panic!("Unexpected reply {:?}", other),
+    }
+    match &replies[1].result {
+        Some(lint_stream_response::Result::Diagnostics(d)) => assert_eq!(d.diagnostics[0].message, "Missing ;"),
+        other => panic!("Unexpected reply {:?}", other),
+    }
+    assert!(matches!(replies[2].result, Some(lint_stream_response::Result::Error(_))));
+}
+
+#[test]
+fn test_grpc_lint_stream_applies_the_input_limit() {
+    let service = GrpcService::start(&["--max-input-bytes", "100"]);
+    let runtime = runtime();
+    let mut client = service.client(&runtime);
+
+    let open = |text: String| LintStreamRequest {
+        message: Some(lint_stream_request::Message::Open(OpenDocument { language: "python".to_string(), text })),
+    };
+    let append = LintStreamRequest {
+        message: Some(lint_stream_request::Message::Edit(EditDocument {
+            edits: vec![TextEdit { start: Some(Position { line: 1, column: 1 }), end: Some(Position { line: 1, column: 1 }), text: "y = 2\n".repeat(10) }],
+        })),
+    };
+    let requests = vec![open("x = 1\n".repeat(50)), open("x = 1\n".to_string()), append.clone(), append];
+
+    let replies = runtime.block_on(async {
+        let mut inbound = client.lint_stream(futures_util::stream::iter(requests)).await.unwrap().into_inner();
+        let mut replies = Vec::new();
+        while let Some(reply) = inbound.message().await.unwrap() {
+            replies.push(reply.result);
+        }
+        replies
+    });
+
+    let error = |reply: &Option<lint_stream_response::Result>| match reply {
+        Some(lint_stream_response::Result::Error(message)) => message.clone(),
+        other => panic!("Unexpected reply {:?}", other),
+    };
+    assert!(error(&replies[0]).contains("exceeding the limit of 100"));
+    assert!(matches!(replies[1], Some(lint_stream_response::Result::Diagnostics(_))));
+    assert!(matches!(replies[2], Some(lint_stream_response::Result::Diagnostics(_))));
+    assert!(error(&replies[3]).contains("exceeding the limit of 100"));
+}
+
+#[test]
+fn test_grpc_requires_api_keys_when_configured() {
+    let keys_file = std::env::temp_dir().join(format!("lint-grpc-keys-{}", std::process::id()));
+    std::fs::write(&keys_file, "team-a secret-a\n").unwrap();
+    let service = GrpcService::start(&["--api-keys-file", keys_file.to_str().unwrap()]);
+    let runtime = runtime();
+    let mut client = service.client(&runtime);
+    let request = || LintRequest { language: "python".to_string(), code: "x = 1\n".to_string(), ..Default::default() };
+
+    let status = runtime.block_on(client.lint(request())).unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+    let mut authorized = tonic::Request::new(request());
+    authorized.metadata_mut().insert("x-api-key", "secret-a".parse().unwrap());
+    assert!(runtime.block_on(client.lint(authorized)).is_ok());
+    std::fs::remove_file(&keys_file).unwrap();
+}
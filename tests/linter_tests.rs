@@ -1,21 +1,28 @@
+
 use std::process::Command;
 use std::fs;
 use std::path::PathBuf;
 use serde_json::from_str;
-use lintymclintface::SyntaxError;
+use std::time::Duration;
+use lintymclintface::{LintOptions, LinterError, Severity, SyntaxError};
+
+/// The `lintymclintface` binary Cargo built for these tests, whatever the profile or target dir.
+fn linter_command() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_lintymclintface"))
+}
+
+/// A path under the temp directory named after `name` and this test process.
+fn scratch_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}-{}", name, std::process::id()))
+}
 
 // Helper function to run linter and get output
 fn run_linter_test(file_path: &PathBuf, language: &str) -> (String, String) {
-    let linter_cmd = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("target")
-        .join("debug") // Use debug build for tests
-        .join("lintymclintface");
-
-    let output = Command::new(&linter_cmd)
+    let output = linter_command()
         .arg("-l")
         .arg(language)
         .arg("-f")
-        .arg(&file_path)
+        .arg(file_path)
         .output()
         .expect("Failed to execute linter command");
 
@@ -37,7 +44,7 @@ fn test_working_java_files() {
     for entry in fs::read_dir(&working_dir).expect("Failed to read tests/working directory") {
         let entry = entry.expect("Failed to read directory entry");
         let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "java") {
+        if path.extension().is_some_and(|ext| ext == "java") {
             println!("Testing working Java file: {:?}", path);
             let (stdout, _) = run_linter_test(&path, "java");
             assert!(
@@ -59,7 +66,7 @@ fn test_failing_java_files() {
     for entry in fs::read_dir(&failing_dir).expect("Failed to read tests/failing directory") {
         let entry = entry.expect("Failed to read directory entry");
         let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "java") {
+        if path.extension().is_some_and(|ext| ext == "java") {
             println!("Testing failing Java file: {:?}", path);
             let (stdout, _) = run_linter_test(&path, "java");
             assert!(
@@ -82,7 +89,7 @@ fn test_working_python_files() {
     for entry in fs::read_dir(&working_dir).expect("Failed to read tests/working directory") {
         let entry = entry.expect("Failed to read directory entry");
         let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "py") {
+        if path.extension().is_some_and(|ext| ext == "py") {
             println!("Testing working Python file: {:?}", path);
             let (stdout, _) = run_linter_test(&path, "python");
             assert!(
@@ -104,12 +111,12 @@ fn test_failing_python_files() {
     for entry in fs::read_dir(&failing_dir).expect("Failed to read tests/failing directory") {
         let entry = entry.expect("Failed to read directory entry");
         let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "py") {
+        if path.extension().is_some_and(|ext| ext == "py") {
             println!("Testing failing Python file: {:?}", path);
             let (stdout, _) = run_linter_test(&path, "python");
 
             let errors: Vec<SyntaxError> = from_str(&stdout)
-                .expect(&format!("Failed to parse JSON output for {:?}: {}", path, stdout));
+                .unwrap_or_else(|_| panic!("Failed to parse JSON output for {:?}: {}", path, stdout));
 
             if path.file_name().unwrap() == "syntax_error_python.py" {
                 assert_eq!(errors.len(), 1);
@@ -138,7 +145,7 @@ fn test_working_r_files() {
     for entry in fs::read_dir(&working_dir).expect("Failed to read tests/working directory") {
         let entry = entry.expect("Failed to read directory entry");
         let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "R") {
+        if path.extension().is_some_and(|ext| ext == "R") {
             println!("Testing working R file: {:?}", path);
             let (stdout, _) = run_linter_test(&path, "r");
             assert!(
@@ -160,12 +167,12 @@ fn test_failing_r_files() {
     for entry in fs::read_dir(&failing_dir).expect("Failed to read tests/failing directory") {
         let entry = entry.expect("Failed to read directory entry");
         let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "R") {
+        if path.extension().is_some_and(|ext| ext == "R") {
             println!("Testing failing R file: {:?}", path);
             let (stdout, _) = run_linter_test(&path, "r");
 
             let errors: Vec<SyntaxError> = from_str(&stdout)
-                .expect(&format!("Failed to parse JSON output for {:?}: {}", path, stdout));
+                .unwrap_or_else(|_| panic!("Failed to parse JSON output for {:?}: {}", path, stdout));
 
             if path.file_name().unwrap() == "syntax_error_r.R" {
                 assert_eq!(errors.len(), 1);
@@ -183,4 +190,395 @@ fn test_failing_r_files() {
             }
         }
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_unix_format_output() {
+    let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("failing")
+        .join("MissingSemicolon.java");
+
+    let output = linter_command()
+        .arg("--format")
+        .arg("unix")
+        .arg("-l")
+        .arg("java")
+        .arg("-f")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute linter command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    assert_eq!(
+        stdout.trim(),
+        format!("{}:3:44: error: Missing ; [java/missing-token]", file_path.display())
+    );
+}
+
+#[test]
+fn test_ndjson_format_output() {
+    let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("working")
+        .join("HelloWorld.java");
+
+    let output = linter_command()
+        .arg("--format")
+        .arg("ndjson")
+        .arg("-l")
+        .arg("java")
+        .arg("-f")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute linter command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let record: serde_json::Value = from_str(stdout.trim()).expect("Expected one JSON object per line");
+    assert_eq!(record["path"], file_path.display().to_string());
+    assert_eq!(record["errors"], serde_json::json!([]));
+}
+
+#[test]
+fn test_lint_with_options_caps_diagnostics() {
+    let code = "x <- 1\ny <- 2\nz <- 3\n";
+    let all = lintymclintface::lint("r", code).unwrap();
+    assert_eq!(all.len(), 3);
+
+    let options = LintOptions { max_diagnostics: Some(2), ..LintOptions::default() };
+    let report = lintymclintface::lint_with_options("r", code, &options).unwrap();
+    assert_eq!(report.errors.len(), 2);
+    assert!(report.truncated);
+
+    let options = LintOptions { max_diagnostics: Some(3), ..LintOptions::default() };
+    assert!(!lintymclintface::lint_with_options("r", code, &options).unwrap().truncated);
+}
+
+#[test]
+fn test_lint_with_options_selects_rules_and_severity() {
+    let code = "print(1)\nx = (\n";
+    let rules = |report: lintymclintface::LintReport| report.errors.into_iter().map(|e| e.rule).collect::<Vec<_>>();
+    let all = rules(lintymclintface::lint_with_options("python", code, &LintOptions::default()).unwrap());
+    assert!(all.contains(&"python/syntax-error".to_string()) && all.contains(&"python/print-statement".to_string()));
+
+    let only = LintOptions { rules: Some(vec!["print-statement".to_string()]), ..LintOptions::default() };
+    assert_eq!(rules(lintymclintface::lint_with_options("python", code, &only).unwrap()), ["python/print-statement"]);
+
+    let disabled = LintOptions { disable: vec!["python/print-statement".to_string()], ..LintOptions::default() };
+    assert!(!rules(lintymclintface::lint_with_options("python", code, &disabled).unwrap()).contains(&"python/print-statement".to_string()));
+
+    let errors_only = LintOptions { min_severity: Some(Severity::Error), ..LintOptions::default() };
+    let report = lintymclintface::lint_with_options("python", code, &errors_only).unwrap();
+    assert!(!report.errors.is_empty() && report.errors.iter().all(|e| e.severity == Severity::Error));
+
+    let unknown = LintOptions { rules: Some(vec!["arrow-assignment".to_string()]), ..LintOptions::default() };
+    let result = lintymclintface::lint_with_options("python", code, &unknown);
+    assert!(matches!(result, Err(LinterError::InvalidOption(_))), "{:?}", result);
+}
+
+#[test]
+fn test_lint_with_options_checks_target_version() {
+    let target = |version: &str| LintOptions { target_version: Some(version.parse().unwrap()), ..LintOptions::default() };
+    let target_errors = |language: &str, code: &str, version: &str| {
+        lintymclintface::lint_with_options(language, code, &target(version))
+            .unwrap()
+            .errors
+            .into_iter()
+            .filter(|e| e.rule.ends_with("/target-version"))
+            .map(|e| (e.line, e.message))
+            .collect::<Vec<_>>()
+    };
+
+    let java = "class A {\n    void f() {\n        var x = 1;\n        Runnable r = () -> {};\n    }\n}\n";
+    assert_eq!(
+        target_errors("java", java, "1.7"),
+        [
+            (3, "The `var` type requires Java 10 or later (targeting Java 7)".to_string()),
+            (4, "A lambda expression requires Java 8 or later (targeting Java 7)".to_string()),
+        ]
+    );
+    assert_eq!(target_errors("java", java, "8").len(), 1);
+    assert!(target_errors("java", java, "17").is_empty());
+    assert!(target_errors("java", "record P(int x) {}\n", "11")[0].1.starts_with("A record declaration requires Java 16"));
+
+    assert_eq!(
+        target_errors("python", "print \"hi\"\n", "3.8"),
+        [(1, "The print statement is not supported since Python 3.0 (targeting Python 3.8)".to_string())]
+    );
+    assert!(target_errors("python", "print \"hi\"\n", "2.7").is_empty());
+    assert_eq!(target_errors("python", "if (n := 10) > 5:\n    pass\n", "3.7").len(), 1);
+    assert_eq!(target_errors("python", "x = f\"{y}\"\n", "3.5").len(), 1);
+
+    assert_eq!(target_errors("r", "x |> f()\n", "4.0").len(), 1);
+    assert!(target_errors("r", "x |> f()\n", "4.1.2").is_empty());
+
+    // Without a target version, no version-specific syntax is reported.
+    let report = lintymclintface::lint_with_options("python", "print \"hi\"\n", &LintOptions::default()).unwrap();
+    assert!(report.errors.iter().all(|e| e.rule != "python/target-version"));
+    assert!("three".parse::<lintymclintface::TargetVersion>().is_err());
+}
+
+#[test]
+fn test_lint_with_options_times_out() {
+    let code = "class A { void f() { int x = 1; } }\n".repeat(100_000);
+    let options = LintOptions { timeout: Some(Duration::from_micros(1)), ..LintOptions::default() };
+    let result = lintymclintface::lint_with_options("java", &code, &options);
+    assert!(matches!(result, Err(LinterError::Timeout(_))), "{:?}", result);
+}
+
+#[test]
+fn test_max_diagnostics_flag_marks_ndjson_truncated() {
+    let dir = scratch_path("lint-max-diagnostics");
+    fs::create_dir_all(&dir).unwrap();
+    let file_path = dir.join("assign.R");
+    fs::write(&file_path, "x <- 1\ny <- 2\n").unwrap();
+
+    let output = linter_command()
+        .args(["--format", "ndjson", "--max-diagnostics", "1", "-l", "r", "-f"])
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute linter command");
+    fs::remove_dir_all(&dir).unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let record: serde_json::Value = from_str(stdout.trim()).expect("Expected one JSON object per line");
+    assert_eq!(record["errors"].as_array().unwrap().len(), 1);
+    assert_eq!(record["truncated"], true);
+}
+
+#[test]
+fn test_max_input_bytes_flag_rejects_large_files() {
+    let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("working")
+        .join("HelloWorld.java");
+
+    let output = linter_command()
+        .args(["--max-input-bytes", "10", "-l", "java", "-f"])
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute linter command");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("exceeding the limit of 10"));
+}
+
+#[test]
+fn test_cache_dir_reuses_results_for_unchanged_files() {
+    let dir = scratch_path("lint-cache-dir");
+    let cache_dir = dir.join("cache");
+    fs::create_dir_all(&dir).unwrap();
+    let file_path = dir.join("assign.R");
+    fs::write(&file_path, "x <- 1\n").unwrap();
+    let run = || {
+        let output = linter_command()
+            .arg("--cache-dir")
+            .arg(&cache_dir)
+            .args(["-l", "r", "-f"])
+            .arg(&file_path)
+            .output()
+            .expect("Failed to execute linter command");
+        from_str::<Vec<SyntaxError>>(&String::from_utf8_lossy(&output.stdout)).unwrap()
+    };
+
+    assert_eq!(run()[0].rule, "r/arrow-assignment");
+    let key = lintymclintface::cache::CacheKey::new("r", "x <- 1\n", &LintOptions { timeout: Some(Duration::from_secs(10)), ..LintOptions::default() });
+    let entry = cache_dir.join(&key.as_str()[..2]).join(format!("{}.json", &key.as_str()[2..]));
+    assert!(entry.exists(), "No cache entry at {}", entry.display());
+
+    // A second run is answered from the cache, not by linting the file again.
+    let planted = fs::read_to_string(&entry).unwrap().replace("r/arrow-assignment", "r/from-cache");
+    fs::write(&entry, planted).unwrap();
+    assert_eq!(run()[0].rule, "r/from-cache");
+
+    // Changing the file changes the key.
+    fs::write(&file_path, "x <- 2\n").unwrap();
+    assert_eq!(run()[0].rule, "r/arrow-assignment");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_debug_logging_keeps_stdout_to_results() {
+    let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("failing")
+        .join("MissingSemicolon.java");
+
+    for format in ["text", "json"] {
+        let output = linter_command()
+            .env("RUST_LOG", "debug")
+            .args(["--log-format", format, "-l", "java", "-f"])
+            .arg(&file_path)
+            .output()
+            .expect("Failed to execute linter command");
+        let errors: Vec<SyntaxError> = from_str(&String::from_utf8_lossy(&output.stdout)).expect("Expected only results on stdout");
+        assert_eq!(errors[0].rule, "java/missing-token");
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Linting java file"), "{}", stderr);
+        if format == "json" {
+            for line in stderr.lines() {
+                let event: serde_json::Value = from_str(line).unwrap_or_else(|_| panic!("Not a JSON log line: {}", line));
+                assert!(event["level"].is_string() && event["fields"]["message"].is_string(), "{}", line);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_log_file_receives_the_logs() {
+    let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("working")
+        .join("HelloWorld.java");
+    let log_file = scratch_path("lint-log-file").with_extension("log");
+
+    let output = linter_command()
+        .env("RUST_LOG", "info")
+        .arg("--log-file")
+        .arg(&log_file)
+        .args(["-l", "java", "-f"])
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute linter command");
+    assert!(output.stdout.is_empty(), "{}", String::from_utf8_lossy(&output.stdout));
+    assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+    let logs = fs::read_to_string(&log_file).unwrap();
+    fs::remove_file(&log_file).unwrap();
+    assert!(logs.contains("No syntax errors found."), "{}", logs);
+    assert!(!logs.contains('\x1b'), "{}", logs);
+}
+
+#[test]
+fn test_metrics_file_describes_the_run() {
+    let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("failing")
+        .join("MissingSemicolon.java");
+    let dir = scratch_path("lint-metrics-file");
+    fs::create_dir_all(&dir).unwrap();
+    let metrics_file = dir.join("lint.prom");
+    let run = |language: &str| {
+        linter_command()
+            .arg("--metrics-file")
+            .arg(&metrics_file)
+            .args(["-l", language, "-f"])
+            .arg(&file_path)
+            .output()
+            .expect("Failed to execute linter command")
+    };
+
+    assert!(run("java").status.success());
+    let metrics = fs::read_to_string(&metrics_file).unwrap();
+    assert!(metrics.contains("# TYPE lint_files_total counter\nlint_files_total{language=\"java\"} 1\n"), "{}", metrics);
+    assert!(metrics.contains("lint_diagnostics_total{language=\"java\",rule=\"java/missing-token\",severity=\"error\"} 1\n"), "{}", metrics);
+    assert!(metrics.contains("\nlint_run_duration_seconds "), "{}", metrics);
+    assert!(metrics.contains("\nlint_run_last_completion_timestamp_seconds "), "{}", metrics);
+
+    // Failures are counted too, without the made-up language becoming a label.
+    assert!(!run("cobol").status.success());
+    let metrics = fs::read_to_string(&metrics_file).unwrap();
+    assert!(metrics.contains("lint_parse_failures_total{language=\"unknown\",reason=\"unsupported_language\"} 1\n"), "{}", metrics);
+    assert!(!metrics.contains("cobol"), "{}", metrics);
+
+    // Oversized files are input errors, not parse failures.
+    let output = linter_command()
+        .arg("--metrics-file")
+        .arg(&metrics_file)
+        .args(["--max-input-bytes", "1", "-l", "java", "-f"])
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute linter command");
+    assert!(!output.status.success());
+    let metrics = fs::read_to_string(&metrics_file).unwrap();
+    assert!(metrics.contains("lint_input_errors_total{language=\"java\"} 1\n"), "{}", metrics);
+    assert!(!metrics.contains("lint_parse_failures_total{"), "{}", metrics);
+    // Only the finished file is left for the collector to read.
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Runs `corpus run` over `dir` with `args`, returning whether it passed, its stdout and the
+/// JSON summary it wrote.
+fn run_corpus(dir: &std::path::Path, args: &[&str]) -> (bool, String, serde_json::Value) {
+    let summary = dir.with_extension("json");
+    let output = linter_command()
+        .args(["corpus", "run"])
+        .arg(dir)
+        .arg("--json")
+        .arg(&summary)
+        .args(args)
+        .output()
+        .expect("Failed to execute linter command");
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    (output.status.success(), stdout, from_str(&fs::read_to_string(&summary).unwrap()).unwrap())
+}
+
+#[test]
+fn test_corpus_run_checks_every_file_against_the_expectation() {
+    let dir = scratch_path("lint-corpus-run");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::create_dir_all(dir.join(".git")).unwrap();
+    fs::write(dir.join("src/Clean.java"), "class Clean {}\n").unwrap();
+    fs::write(dir.join("src/Broken.java"), "class Broken { int x = 1 }\n").unwrap();
+    fs::write(dir.join("assign.R"), "x <- 1\n").unwrap();
+    fs::write(dir.join("README.md"), "# not linted\n").unwrap();
+    fs::write(dir.join(".git/Hidden.java"), "class {\n").unwrap();
+
+    let (passed, stdout, summary) = run_corpus(&dir, &[]);
+    assert!(!passed, "{}", stdout);
+    assert!(stdout.contains("FAIL src/Broken.java\n  src/Broken.java:1:"), "{}", stdout);
+    assert!(stdout.contains("3 files: 1 passed, 2 failed"), "{}", stdout);
+    assert_eq!(summary["files"]["src/Clean.java"]["status"], "pass");
+    assert_eq!(summary["files"]["src/Broken.java"]["diagnostics"][0]["rule"], "java/missing-token");
+    assert_eq!(summary["totals"]["files"], 3);
+
+    // Warnings are allowed when only errors are ruled out, and known failures are tolerated.
+    let manifest = dir.with_extension("known-failures");
+    fs::write(&manifest, "# compiles with javac 1.0 only\nsrc/Broken.java\n").unwrap();
+    let (passed, stdout, summary) = run_corpus(&dir, &["--expect", "no-errors", "--known-failures", manifest.to_str().unwrap(), "-j", "2"]);
+    assert!(passed, "{}", stdout);
+    assert_eq!(summary["files"]["assign.R"]["status"], "pass");
+    assert_eq!(summary["files"]["src/Broken.java"]["status"], "known-failure");
+
+    // A known failure that passes again is pointed out, without failing the run.
+    fs::write(dir.join("src/Broken.java"), "class Broken { int x = 1; }\n").unwrap();
+    let (passed, stdout, summary) = run_corpus(&dir, &["--language", "java", "--known-failures", manifest.to_str().unwrap()]);
+    assert!(passed, "{}", stdout);
+    assert!(stdout.contains("FIXED src/Broken.java"), "{}", stdout);
+    assert_eq!(summary["totals"]["files"], 2);
+    assert_eq!(summary["totals"]["fixed"], 1);
+
+    fs::remove_file(&manifest).unwrap();
+    fs::remove_file(dir.with_extension("json")).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_corpus_run_compares_with_the_previous_run() {
+    let dir = scratch_path("lint-corpus-previous");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("A.java"), "class A {}\n").unwrap();
+    fs::write(dir.join("B.java"), "class B { int x = 1 }\n").unwrap();
+    let previous = dir.with_extension("previous.json");
+    let html = dir.with_extension("html");
+
+    // Without a previous run yet, there is nothing to compare with.
+    let (_, _, summary) = run_corpus(&dir, &["--previous", previous.to_str().unwrap()]);
+    assert!(summary.get("comparison").is_none());
+    fs::write(&previous, serde_json::to_vec(&summary).unwrap()).unwrap();
+
+    fs::write(dir.join("A.java"), "class A { void f() { return } }\n").unwrap();
+    fs::write(dir.join("B.java"), "class B { int x = 1; }\n").unwrap();
+    let (passed, stdout, summary) = run_corpus(&dir, &["--previous", previous.to_str().unwrap(), "--html", html.to_str().unwrap()]);
+    assert!(!passed);
+    assert!(stdout.contains(": 1 regressed, 1 fixed, 0 with changed diagnostics\n  regressed A.java\n  fixed B.java\n"), "{}", stdout);
+    assert_eq!(summary["comparison"]["regressions"], serde_json::json!(["A.java"]));
+    assert_eq!(summary["comparison"]["fixes"], serde_json::json!(["B.java"]));
+    let report = fs::read_to_string(&html).unwrap();
+    assert!(report.contains("<code>A.java</code>") && report.contains("class=\"regression\""), "{}", report);
+
+    for file in [previous, html, dir.with_extension("json")] {
+        fs::remove_file(file).unwrap();
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
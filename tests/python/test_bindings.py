@@ -0,0 +1,69 @@
+"""Tests for the Python bindings.
+
+Build and install the extension into the current environment first:
+
+    maturin develop
+    pytest tests/python
+"""
+
+from pathlib import Path
+
+import pytest
+
+import lintymclintface
+
+TESTS_DIR = Path(__file__).resolve().parent.parent
+
+
+def test_lint_reports_missing_semicolon():
+    code = (TESTS_DIR / "failing" / "MissingSemicolon.java").read_text()
+    diagnostics = lintymclintface.lint(code, "java")
+    assert len(diagnostics) == 1
+    diagnostic = diagnostics[0]
+    assert (diagnostic.line, diagnostic.column) == (3, 44)
+    assert diagnostic.message == "Missing ;"
+    assert diagnostic.severity == "error"
+    assert diagnostic.rule == "java/missing-token"
+
+
+def test_lint_clean_code_returns_empty_list():
+    assert lintymclintface.lint("x = 1\n", "python") == []
+
+
+def test_lint_timeout_raises_timeout_error():
+    code = "class A { void f() { int x = 1; } }\n" * 100_000
+    with pytest.raises(TimeoutError):
+        lintymclintface.lint(code, "java", timeout_ms=1)
+
+
+def test_to_dict_matches_cli_json():
+    diagnostics = lintymclintface.lint("print('hi')\n", "python")
+    assert [d.to_dict() for d in diagnostics] == [
+        {
+            "line": 1,
+            "column": 1,
+            "message": "Use of print statements is discouraged",
+            "severity": "warning",
+            "rule": "python/print-statement",
+        }
+    ]
+
+
+def test_lint_file_detects_language_from_extension():
+    diagnostics = lintymclintface.lint_file(str(TESTS_DIR / "failing" / "syntax_error_r.R"))
+    assert [(d.line, d.column, d.message) for d in diagnostics] == [(1, 19, "Missing )")]
+
+
+def test_lint_file_working_files_are_clean():
+    for path in (TESTS_DIR / "working").iterdir():
+        assert lintymclintface.lint_file(str(path)) == [], path
+
+
+def test_unsupported_language_raises_value_error():
+    with pytest.raises(ValueError, match="cobol"):
+        lintymclintface.lint("", "cobol")
+
+
+def test_lint_file_missing_file_raises_os_error():
+    with pytest.raises(OSError):
+        lintymclintface.lint_file(str(TESTS_DIR / "does_not_exist.py"))
@@ -0,0 +1,799 @@
+mod common;
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::process::Command;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use common::{free_port, service_command, Service};
+use serde_json::{json, Value};
+
+#[test]
+fn test_lint_endpoint() {
+    let service = Service::start(&[]);
+    let response = service.post_json(
+        "/lint",
+        &json!({"language": "java", "code": "public class Test {\n    int x = 1\n}\n"}),
+    );
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("content-type"), Some("application/json"));
+    let errors = response.json();
+    assert_eq!(errors[0]["line"], 2);
+    assert_eq!(errors[0]["rule"], "java/missing-token");
+}
+
+#[test]
+fn test_batch_endpoint_preserves_order_and_reports_item_errors() {
+    let service = Service::start(&[]);
+    let response = service.post_json(
+        "/lint/batch",
+        &json!([
+            {"path": "src/Ok.java", "code": "class Ok {}\n"},
+            {"path": "script.py", "code": "def f(:\n    pass\n"},
+            {"path": "analysis.txt", "language": "r", "code": "x <- 1\n"},
+            {"path": "README.md", "code": "# hi\n"},
+            {"path": "main.go", "language": "go", "code": "package main\n"}
+        ]),
+    );
+    assert_eq!(response.status, 200);
+    let results = response.json();
+    let results = results.as_array().unwrap();
+    let paths: Vec<&str> = results.iter().map(|r| r["path"].as_str().unwrap()).collect();
+    assert_eq!(paths, ["src/Ok.java", "script.py", "analysis.txt", "README.md", "main.go"]);
+
+    assert_eq!(results[0]["language"], "java");
+    assert_eq!(results[0]["errors"], json!([]));
+    assert!(results[0].get("error").is_none());
+    assert_eq!(results[1]["language"], "python");
+    assert_eq!(results[1]["errors"][0]["severity"], "error");
+    assert_eq!(results[2]["errors"][0]["rule"], "r/arrow-assignment");
+    assert!(results[3]["error"].as_str().unwrap().contains("Cannot detect the language"));
+    assert!(results[4]["error"].as_str().unwrap().contains("Unsupported language: go"));
+}
+
+#[test]
+fn test_batch_endpoint_enforces_size_limit() {
+    let service = Service::start(&["--max-batch-size", "2"]);
+    let item = json!({"path": "a.py", "code": "x = 1\n"});
+    let response = service.post_json("/lint/batch", &json!([item, item, item]));
+    assert_eq!(response.status, 413);
+    assert!(response.json()["error"].as_str().unwrap().contains("limit of 2"));
+
+    let response = service.post_json("/lint/batch", &json!([item, item]));
+    assert_eq!(response.status, 200);
+}
+
+#[test]
+fn test_bulk_endpoints_apply_the_input_limit_to_each_file() {
+    let service = Service::start(&["--max-input-bytes", "100", "--max-bulk-bytes", "2000"]);
+    let small = json!({"path": "a.py", "code": "x = 1\n"});
+    let big = json!({"path": "b.py", "code": "x = 1\n".repeat(50)});
+    let response = service.post_json("/lint/batch", &json!([small, big]));
+    assert_eq!(response.status, 200);
+    let results = response.json();
+    assert!(results[0].get("error").is_none(), "{}", results);
+    assert_eq!(results[1]["error"], "Code of 300 bytes exceeds the limit of 100");
+
+    let archive = zip(&[("a.py", "x = 1\n"), ("b.py", &"x = 1\n".repeat(50))]);
+    let report = service.request_bytes("POST", "/lint/archive", "application/zip", &archive).json();
+    assert_eq!(report["files"]["b.py"]["error"], "Code of 300 bytes exceeds the limit of 100");
+
+    // The whole body is capped by --max-bulk-bytes instead.
+    let many: Vec<Value> = (0..100).map(|_| small.clone()).collect();
+    assert_eq!(service.post_json("/lint/batch", &json!(many)).status, 413);
+    assert_eq!(service.post_json("/jobs", &json!(many)).status, 413);
+}
+
+fn tar_gz(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, contents.as_bytes()).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (path, contents) in files {
+        writer.start_file(*path, zip::write::SimpleFileOptions::default()).unwrap();
+        writer.write_all(contents.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn test_archive_endpoint_lints_tar_gz() {
+    let service = Service::start(&[]);
+    let archive = tar_gz(&[
+        ("project/src/Main.java", "public class Main {\n    int x = 1\n}\n"),
+        ("project/scripts/ok.py", "x = 1\n"),
+        ("project/analysis/plot.R", "x <- 1\n"),
+        ("project/README.md", "# Project\n"),
+    ]);
+    let response = service.request_bytes("POST", "/lint/archive", "application/gzip", &archive);
+    assert_eq!(response.status, 200);
+    let report = response.json();
+    assert_eq!(report["files"]["project/src/Main.java"]["language"], "java");
+    assert_eq!(report["files"]["project/src/Main.java"]["errors"][0]["rule"], "java/missing-token");
+    assert_eq!(report["files"]["project/scripts/ok.py"]["errors"], json!([]));
+    assert_eq!(report["files"]["project/analysis/plot.R"]["errors"][0]["rule"], "r/arrow-assignment");
+    assert_eq!(report["skipped"]["project/README.md"], "unsupported file type");
+    assert_eq!(report["summary"], json!({"files_linted": 3, "files_skipped": 1, "errors": 2}));
+}
+
+#[test]
+fn test_archive_endpoint_rejects_path_traversal_in_zip() {
+    let service = Service::start(&[]);
+    let archive = zip(&[
+        ("ok.py", "x = 1\n"),
+        ("../evil.py", "x = 1\n"),
+        ("/etc/evil.py", "x = 1\n"),
+        ("a/../../evil.py", "x = 1\n"),
+    ]);
+    let response = service.request_bytes("POST", "/lint/archive", "application/zip", &archive);
+    assert_eq!(response.status, 200);
+    let report = response.json();
+    assert_eq!(report["files"].as_object().unwrap().keys().collect::<Vec<_>>(), ["ok.py"]);
+    for path in ["../evil.py", "/etc/evil.py", "a/../../evil.py"] {
+        assert_eq!(report["skipped"][path], "path is absolute or escapes the archive root");
+    }
+}
+
+#[test]
+fn test_archive_endpoint_skips_entries_that_normalize_to_the_same_path() {
+    let service = Service::start(&[]);
+    let archive = zip(&[("a/b.py", "print(1)\n"), ("a/./b.py", "x = 1\n"), ("a//b.py", "x = 2\n")]);
+    let report = service.request_bytes("POST", "/lint/archive", "application/zip", &archive).json();
+    assert_eq!(report["files"]["a/b.py"]["errors"][0]["rule"], "python/print-statement", "{}", report);
+    for path in ["a/./b.py", "a//b.py"] {
+        assert_eq!(report["skipped"][path], "duplicate of an earlier entry for a/b.py");
+    }
+    assert_eq!(report["summary"]["files_linted"], 1);
+    assert_eq!(report["summary"]["files_skipped"], 2);
+}
+
+#[test]
+fn test_archive_endpoint_enforces_limits() {
+    let service = Service::start(&["--max-archive-bytes", "100", "--max-archive-files", "3"]);
+
+    let big = "x = 1\n".repeat(50);
+    let response = service.request_bytes("POST", "/lint/archive", "application/zip", &zip(&[("big.py", &big)]));
+    assert_eq!(response.status, 413);
+    assert!(response.json()["error"].as_str().unwrap().contains("100 bytes"));
+
+    let many = tar_gz(&[("a.py", ""), ("b.py", ""), ("c.py", ""), ("d.py", "")]);
+    let response = service.request_bytes("POST", "/lint/archive", "application/gzip", &many);
+    assert_eq!(response.status, 413);
+    assert!(response.json()["error"].as_str().unwrap().contains("3 files"));
+
+    let response = service.request_bytes("POST", "/lint/archive", "application/octet-stream", b"not an archive");
+    assert_eq!(response.status, 400);
+}
+
+/// Polls `GET /jobs/{id}` until the job is no longer queued or running.
+fn wait_for_job(service: &Service, id: &str) -> Value {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let job = service.request("GET", &format!("/jobs/{}", id), None).json();
+        if job["status"] != "queued" && job["status"] != "running" {
+            return job;
+        }
+        assert!(Instant::now() < deadline, "Job {} did not finish: {}", id, job);
+        sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn test_jobs_lint_files_asynchronously() {
+    let service = Service::start(&[]);
+    let response = service.post_json(
+        "/jobs",
+        &json!([
+            {"path": "a.py", "code": "x = 1\n"},
+            {"path": "B.java", "code": "class B { int x = 1 }\n"},
+            {"path": "c.R", "code": "x <- 1\n"}
+        ]),
+    );
+    assert_eq!(response.status, 202);
+    let id = response.json()["id"].as_str().unwrap().to_string();
+    assert_eq!(response.header("location"), Some(format!("/jobs/{}", id).as_str()));
+
+    let job = wait_for_job(&service, &id);
+    assert_eq!(job["status"], "completed");
+    assert_eq!(job["progress"], json!({"completed": 3, "total": 3}));
+    let paths: Vec<&str> = job["results"].as_array().unwrap().iter().map(|r| r["path"].as_str().unwrap()).collect();
+    assert_eq!(paths, ["a.py", "B.java", "c.R"]);
+    assert_eq!(job["results"][1]["errors"][0]["rule"], "java/missing-token");
+
+    assert_eq!(service.request("DELETE", &format!("/jobs/{}", id), None).status, 204);
+    assert_eq!(service.request("GET", &format!("/jobs/{}", id), None).status, 404);
+    assert_eq!(service.request("DELETE", &format!("/jobs/{}", id), None).status, 404);
+}
+
+#[test]
+fn test_jobs_accept_archives() {
+    let service = Service::start(&[]);
+    let archive = zip(&[("src/ok.py", "x = 1\n"), ("notes.txt", "hello\n")]);
+    let response = service.request_bytes("POST", "/jobs", "application/zip", &archive);
+    assert_eq!(response.status, 202);
+
+    let job = wait_for_job(&service, response.json()["id"].as_str().unwrap());
+    assert_eq!(job["status"], "completed");
+    assert_eq!(job["results"][0], json!({"path": "notes.txt", "language": null, "errors": [], "error": "unsupported file type"}));
+    assert_eq!(job["results"][1]["path"], "src/ok.py");
+    // Skipped entries count towards the progress, like the results they appear in.
+    assert_eq!(job["progress"], json!({"completed": 2, "total": 2}));
+
+    let response = service.request_bytes("POST", "/jobs", "application/zip", b"not an archive");
+    let job = wait_for_job(&service, response.json()["id"].as_str().unwrap());
+    assert_eq!(job["status"], "failed");
+    assert!(job["error"].as_str().unwrap().contains("Unsupported archive format"));
+}
+
+#[test]
+fn test_finished_jobs_expire_after_ttl() {
+    let service = Service::start(&["--job-ttl-secs", "0"]);
+    let response = service.post_json("/jobs", &json!([{"path": "a.py", "code": "x = 1\n"}]));
+    let id = response.json()["id"].as_str().unwrap().to_string();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while service.request("GET", &format!("/jobs/{}", id), None).status != 404 {
+        assert!(Instant::now() < deadline, "Job {} never expired", id);
+        sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn test_jobs_are_refused_once_max_jobs_are_active() {
+    let service = Service::start(&["--max-jobs", "1"]);
+    let slow = "class A { void f() { int x = 1; } }\n".repeat(100_000);
+    let files: Vec<Value> = (0..8).map(|i| json!({"path": format!("A{}.java", i), "code": slow})).collect();
+    let response = service.post_json("/jobs", &json!(files));
+    assert_eq!(response.status, 202);
+    let id = response.json()["id"].as_str().unwrap().to_string();
+
+    let response = service.post_json("/jobs", &json!([{"path": "a.py", "code": "x = 1\n"}]));
+    assert_eq!(response.status, 503, "{}", response.body);
+    assert_eq!(response.header("retry-after"), Some("1"));
+
+    // Cancelling the running job frees its slot.
+    assert_eq!(service.request("DELETE", &format!("/jobs/{}", id), None).status, 204);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while service.post_json("/jobs", &json!([{"path": "a.py", "code": "x = 1\n"}])).status != 202 {
+        assert!(Instant::now() < deadline, "The cancelled job kept its slot");
+        sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn test_batch_endpoint_streams_ndjson() {
+    let service = Service::start(&[]);
+    let body = json!([
+        {"path": "a.py", "code": "x = 1\n"},
+        {"path": "B.java", "code": "class B { int x = 1 }\n"}
+    ]);
+    let response = service.send(
+        "POST",
+        "/lint/batch",
+        &[("Content-Type", "application/json"), ("Accept", "application/x-ndjson")],
+        body.to_string().as_bytes(),
+    );
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("content-type"), Some("application/x-ndjson"));
+    let mut lines: Vec<Value> = response.body.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    lines.sort_by_key(|l| l["path"].as_str().unwrap().to_string());
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["path"], "B.java");
+    assert_eq!(lines[0]["errors"][0]["rule"], "java/missing-token");
+    assert_eq!(lines[1]["errors"], json!([]));
+}
+
+#[test]
+fn test_job_events_stream_results_and_status() {
+    let service = Service::start(&[]);
+    let archive = zip(&[("ok.py", "x = 1\n"), ("Bad.java", "class Bad { int x = 1 }\n"), ("notes.txt", "")]);
+    let response = service.request_bytes("POST", "/jobs", "application/zip", &archive);
+    let id = response.json()["id"].as_str().unwrap().to_string();
+
+    let response = service.request("GET", &format!("/jobs/{}/events", id), None);
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("content-type"), Some("text/event-stream"));
+    let events: Vec<(&str, Value)> = response
+        .body
+        .split("\n\n")
+        .filter(|e| !e.is_empty())
+        .map(|e| {
+            let (event, data) = e.split_once('\n').unwrap();
+            (event.strip_prefix("event: ").unwrap(), serde_json::from_str(data.strip_prefix("data: ").unwrap()).unwrap())
+        })
+        .collect();
+
+    assert_eq!(events.len(), 4);
+    let mut paths: Vec<&str> = events[..3].iter().map(|(kind, data)| {
+        assert_eq!(*kind, "result");
+        data["path"].as_str().unwrap()
+    }).collect();
+    paths.sort();
+    assert_eq!(paths, ["Bad.java", "notes.txt", "ok.py"]);
+    assert_eq!(events[3].0, "status");
+    assert_eq!(events[3].1["status"], "completed");
+    assert!(events[3].1.get("results").is_none());
+
+    assert_eq!(service.request("GET", "/jobs/unknown/events", None).status, 404);
+}
+
+#[test]
+fn test_websocket_live_lint_session() {
+    let service = Service::start(&[]);
+    let (mut socket, _) = tungstenite::connect(format!("ws://127.0.0.1:{}/ws", service.port)).expect("WebSocket handshake failed");
+    let mut send = |message: Value| -> Value {
+        socket.send(tungstenite::Message::text(message.to_string())).unwrap();
+        loop {
+            if let tungstenite::Message::Text(text) = socket.read().unwrap() {
+                return serde_json::from_str(text.as_str()).unwrap();
+            }
+        }
+    };
+
+    let reply = send(json!({"type": "open", "language": "java", "text": "class A {\n    int x = 1;\n}\n"}));
+    assert_eq!(reply, json!({"type": "diagnostics", "version": 1, "diagnostics": []}));
+
+    let reply = send(json!({"type": "edit", "edits": [
+        {"start": {"line": 2, "column": 14}, "end": {"line": 2, "column": 15}, "text": ""}
+    ]}));
+    assert_eq!(reply["version"], 2);
+    assert_eq!(reply["diagnostics"][0]["message"], "Missing ;");
+    assert_eq!(reply["diagnostics"][0]["line"], 2);
+
+    let reply = send(json!({"type": "edit", "edits": [
+        {"start": {"line": 9, "column": 1}, "end": {"line": 9, "column": 1}, "text": "x"}
+    ]}));
+    assert_eq!(reply["type"], "error");
+
+    // A column past the end of the address space is refused rather than overflowing.
+    let reply = send(json!({"type": "edit", "edits": [
+        {"start": {"line": 2, "column": u64::MAX}, "end": {"line": 2, "column": u64::MAX}, "text": "x"}
+    ]}));
+    assert_eq!(reply["type"], "error", "{}", reply);
+    assert_eq!(send(json!({"type": "edit", "edits": []}))["version"], 3);
+
+    let reply = send(json!({"type": "bogus"}));
+    assert_eq!(reply["type"], "error");
+}
+
+#[test]
+fn test_websocket_edits_cannot_grow_the_document_past_the_input_limit() {
+    let service = Service::start(&["--max-input-bytes", "300"]);
+    let (mut socket, _) = tungstenite::connect(format!("ws://127.0.0.1:{}/ws", service.port)).expect("WebSocket handshake failed");
+    let mut send = |message: Value| -> Value {
+        socket.send(tungstenite::Message::text(message.to_string())).unwrap();
+        loop {
+            if let tungstenite::Message::Text(text) = socket.read().unwrap() {
+                return serde_json::from_str(text.as_str()).unwrap();
+            }
+        }
+    };
+
+    assert_eq!(send(json!({"type": "open", "language": "python", "text": "x = 1\n"}))["type"], "diagnostics");
+    // Each edit fits in a message, but together they would grow the document past the limit.
+    let append = json!({"type": "edit", "edits": [
+        {"start": {"line": 1, "column": 1}, "end": {"line": 1, "column": 1}, "text": "y = 2\n".repeat(25)}
+    ]});
+    assert_eq!(send(append.clone())["type"], "diagnostics");
+    let reply = send(append.clone());
+    assert_eq!(reply["type"], "error", "{}", reply);
+    assert!(reply["message"].as_str().unwrap().contains("exceeding the limit of 300"), "{}", reply);
+    // The refused edit left the document as it was.
+    let reply = send(json!({"type": "edit", "edits": []}));
+    assert_eq!(reply["version"], 3);
+}
+
+#[test]
+fn test_lint_endpoint_enforces_input_and_diagnostic_limits() {
+    let service = Service::start(&["--max-input-bytes", "200", "--max-diagnostics", "1"]);
+    let response = service.post_json("/lint", &json!({"language": "r", "code": "x <- 1\n".repeat(50)}));
+    assert_eq!(response.status, 413);
+    assert!(response.json()["error"].as_str().unwrap().contains("limit: 200 bytes"), "{}", response.body);
+    let response = service.request("POST", "/lint", Some("{not json"));
+    assert_eq!(response.status, 400);
+    assert!(response.json()["error"].is_string(), "{}", response.body);
+
+    let response = service.post_json("/lint", &json!({"language": "r", "code": "x <- 1\ny <- 2\n"}));
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("x-lint-truncated"), Some("true"));
+    assert_eq!(response.json().as_array().unwrap().len(), 1);
+
+    let response = service.post_json("/lint/batch", &json!([{"path": "a.R", "code": "x <- 1\ny <- 2\n"}, {"path": "b.R", "code": "x <- 1\n"}]));
+    assert_eq!(response.status, 200);
+    let results = response.json();
+    assert_eq!(results[0]["errors"].as_array().unwrap().len(), 1);
+    assert_eq!(results[0]["truncated"], true);
+    assert!(results[1].get("truncated").is_none());
+}
+
+#[test]
+fn test_lint_endpoint_reports_parse_timeouts() {
+    let service = Service::start(&["--parse-timeout-ms", "1"]);
+    let code = "class A { void f() { int x = 1; } }\n".repeat(100_000);
+    let response = service.post_json("/lint", &json!({"language": "java", "code": code}));
+    assert_eq!(response.status, 422);
+    assert!(response.json()[0]["message"].as_str().unwrap().contains("timed out"));
+}
+
+#[test]
+fn test_api_keys_are_required_when_configured() {
+    let keys_file = std::env::temp_dir().join(format!("lint-api-keys-{}", std::process::id()));
+    std::fs::write(&keys_file, "# client key\nteam-a secret-a\nteam-b secret-b\n").unwrap();
+    let service = Service::start(&["--api-keys-file", keys_file.to_str().unwrap()]);
+    let body = json!({"language": "python", "code": "x = 1\n"}).to_string();
+
+    let response = service.request("POST", "/lint", Some(&body));
+    assert_eq!(response.status, 401);
+    assert_eq!(response.header("www-authenticate"), Some("Bearer"));
+
+    let response = service.send("POST", "/lint", &[("Content-Type", "application/json"), ("X-API-Key", "wrong")], body.as_bytes());
+    assert_eq!(response.status, 401);
+
+    let response = service.send("POST", "/lint", &[("Content-Type", "application/json"), ("Authorization", "Bearer secret-a")], body.as_bytes());
+    assert_eq!(response.status, 200);
+    let response = service.send("POST", "/lint", &[("Content-Type", "application/json"), ("X-API-Key", "secret-b")], body.as_bytes());
+    assert_eq!(response.status, 200);
+
+    let metrics = service.request("GET", "/metrics", None);
+    assert_eq!(metrics.status, 200);
+    assert!(metrics.body.contains("lint_client_requests_total{client=\"team-a\"} 1"));
+    assert!(metrics.body.contains("lint_client_requests_total{client=\"team-b\"} 1"));
+    std::fs::remove_file(&keys_file).unwrap();
+}
+
+#[test]
+fn test_rate_limit_rejects_excess_requests_per_client() {
+    let keys_file = std::env::temp_dir().join(format!("lint-rate-limit-keys-{}", std::process::id()));
+    std::fs::write(&keys_file, "team-a secret-a\nteam-b secret-b\n").unwrap();
+    let service = Service::start(&[
+        "--api-keys-file", keys_file.to_str().unwrap(),
+        "--rate-limit", "0.1",
+        "--rate-limit-burst", "2",
+    ]);
+    let body = json!({"language": "python", "code": "x = 1\n"}).to_string();
+    let lint = |key: &str| service.send("POST", "/lint", &[("Content-Type", "application/json"), ("X-API-Key", key)], body.as_bytes());
+
+    assert_eq!(lint("secret-a").status, 200);
+    assert_eq!(lint("secret-a").status, 200);
+    let response = lint("secret-a");
+    assert_eq!(response.status, 429);
+    let retry_after: u64 = response.header("retry-after").unwrap().parse().unwrap();
+    assert!((1..=10).contains(&retry_after));
+
+    // Other clients have their own bucket.
+    assert_eq!(lint("secret-b").status, 200);
+
+    let metrics = service.request("GET", "/metrics", None);
+    assert!(metrics.body.contains("lint_rate_limited_total{client=\"team-a\"} 1"));
+    std::fs::remove_file(&keys_file).unwrap();
+}
+
+#[test]
+fn test_health_readiness_and_version_endpoints() {
+    let keys_file = std::env::temp_dir().join(format!("lint-health-keys-{}", std::process::id()));
+    std::fs::write(&keys_file, "team-a secret-a\n").unwrap();
+    // The probes must work without an API key.
+    let service = Service::start(&["--api-keys-file", keys_file.to_str().unwrap()]);
+
+    let response = service.request("GET", "/healthz", None);
+    assert_eq!(response.status, 200);
+    assert_eq!(response.json()["status"], "ok");
+
+    let response = service.request("GET", "/readyz", None);
+    assert_eq!(response.status, 200);
+    let ready = response.json();
+    assert_eq!(ready["status"], "ready");
+    assert_eq!(ready["languages"], json!({"java": "ok", "python": "ok", "r": "ok"}));
+
+    let response = service.request("GET", "/version", None);
+    assert_eq!(response.status, 200);
+    let version = response.json();
+    assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+    assert!(version["languages"]["java"]["grammar_version"].as_str().unwrap().starts_with("0.20."));
+    assert!(version["languages"]["r"]["rules"].as_array().unwrap().contains(&json!("r/arrow-assignment")));
+    std::fs::remove_file(&keys_file).unwrap();
+}
+
+#[test]
+fn test_readiness_ignores_the_diagnostic_cap() {
+    let service = Service::start(&["--max-diagnostics", "0"]);
+    let response = service.request("GET", "/readyz", None);
+    assert_eq!(response.status, 200, "{}", response.body);
+    assert_eq!(response.json()["status"], "ready");
+}
+
+#[test]
+fn test_sigterm_shuts_down_gracefully() {
+    let mut service = Service::start(&["--shutdown-grace-secs", "5"]);
+    assert_eq!(service.request("GET", "/healthz", None).status, 200);
+
+    let status = Command::new("kill").arg("-TERM").arg(service.child.id().to_string()).status().unwrap();
+    assert!(status.success());
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        if let Some(status) = service.child.try_wait().unwrap() {
+            assert!(status.success(), "Service exited with {}", status);
+            break;
+        }
+        assert!(Instant::now() < deadline, "Service did not exit after SIGTERM");
+        sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn test_api_is_versioned_and_described_by_openapi() {
+    let service = Service::start(&[]);
+    let body = json!({"language": "java", "code": "class A { int x = 1 }\n"});
+    let versioned = service.post_json("/v1/lint", &body);
+    assert_eq!(versioned.status, 200);
+    let alias = service.post_json("/lint", &body);
+    assert_eq!(alias.json(), versioned.json());
+
+    let response = service.post_json("/v1/jobs", &json!([{"path": "a.py", "code": "x = 1\n"}]));
+    assert_eq!(response.status, 202);
+    let id = response.json()["id"].as_str().unwrap().to_string();
+    assert_eq!(response.header("location"), Some(format!("/v1/jobs/{}", id).as_str()));
+
+    let response = service.request("GET", "/openapi.json", None);
+    assert_eq!(response.status, 200);
+    let spec = response.json();
+    assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+    for path in ["/v1/lint", "/v1/parse", "/v1/lint/batch", "/v1/lint/archive", "/v1/jobs", "/v1/jobs/{id}", "/v1/jobs/{id}/events"] {
+        assert!(spec["paths"].get(path).is_some(), "{} is not documented", path);
+    }
+    let lint = &spec["paths"]["/v1/lint"]["post"];
+    assert_eq!(lint["requestBody"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/LintRequest");
+    let syntax_error = &spec["components"]["schemas"]["SyntaxError"];
+    assert_eq!(syntax_error["required"], json!(["line", "column", "message", "severity", "rule"]));
+    assert_eq!(spec["components"]["schemas"]["Severity"]["enum"], json!(["warning", "error"]));
+}
+
+#[test]
+fn test_repeated_lints_are_answered_from_the_cache() {
+    let service = Service::start(&[]);
+    let body = json!({"language": "python", "code": "def f(:\n    pass\n"});
+    let first = service.post_json("/v1/lint", &body);
+    let second = service.post_json("/v1/lint", &body);
+    assert_eq!(first.json(), second.json());
+
+    let metrics = service.request("GET", "/metrics", None).body;
+    assert!(metrics.contains("lint_cache_misses_total 1"), "{}", metrics);
+    assert!(metrics.contains("lint_cache_hits_total 1"), "{}", metrics);
+}
+
+#[test]
+fn test_result_cache_can_be_disabled() {
+    let service = Service::start(&["--cache-entries", "0"]);
+    let body = json!({"language": "python", "code": "x = 1\n"});
+    service.post_json("/v1/lint", &body);
+    service.post_json("/v1/lint", &body);
+    let metrics = service.request("GET", "/metrics", None).body;
+    assert!(metrics.contains("lint_cache_hits_total 0"), "{}", metrics);
+}
+
+#[test]
+fn test_playground_page_and_parse_endpoint() {
+    let keys_file = std::env::temp_dir().join(format!("lint-playground-keys-{}", std::process::id()));
+    std::fs::write(&keys_file, "team-a secret-a\n").unwrap();
+    let service = Service::start(&["--api-keys-file", keys_file.to_str().unwrap()]);
+
+    let page = service.request("GET", "/", None);
+    assert_eq!(page.status, 200);
+    assert!(page.header("content-type").unwrap().starts_with("text/html"));
+    assert!(page.body.contains("/v1/lint") && page.body.contains("/v1/parse"));
+
+    let body = json!({"language": "java", "code": "class A { int x = 1 }\n"}).to_string();
+    let headers = [("Content-Type", "application/json"), ("X-API-Key", "secret-a")];
+    let response = service.send("POST", "/v1/parse", &headers, body.as_bytes());
+    assert_eq!(response.status, 200);
+    let tree = response.json();
+    assert_eq!(tree["kind"], "program");
+    assert_eq!(tree["start"], json!({"line": 1, "column": 1}));
+    let class = &tree["children"][0];
+    assert_eq!(class["kind"], "class_declaration");
+    assert!(class.to_string().contains(r#""error":true"#), "{}", class);
+
+    let body = json!({"language": "cobol", "code": ""}).to_string();
+    let response = service.send("POST", "/v1/parse", &headers, body.as_bytes());
+    assert_eq!(response.status, 400);
+    assert_eq!(response.json()["error"], "Unsupported language: cobol");
+    std::fs::remove_file(&keys_file).unwrap();
+}
+
+#[test]
+fn test_cors_allows_configured_origins() {
+    let service = Service::start(&["--cors-origin", "https://editor.example"]);
+    let preflight = [
+        ("Origin", "https://editor.example"),
+        ("Access-Control-Request-Method", "POST"),
+        ("Access-Control-Request-Headers", "content-type"),
+    ];
+    let response = service.send("OPTIONS", "/v1/lint", &preflight, b"");
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("access-control-allow-origin"), Some("https://editor.example"));
+
+    let body = json!({"language": "python", "code": "x = 1\n"}).to_string();
+    let headers = [("Content-Type", "application/json"), ("Origin", "https://editor.example")];
+    let response = service.send("POST", "/v1/lint", &headers, body.as_bytes());
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("access-control-allow-origin"), Some("https://editor.example"));
+    assert!(response.header("access-control-expose-headers").unwrap().to_ascii_lowercase().contains("x-lint-truncated"));
+
+    let headers = [("Content-Type", "application/json"), ("Origin", "https://elsewhere.example")];
+    let response = service.send("POST", "/v1/lint", &headers, body.as_bytes());
+    assert_eq!(response.header("access-control-allow-origin"), None);
+}
+
+#[test]
+fn test_bind_restricts_the_listening_interface() {
+    let service = Service::start(&["--bind", "127.0.0.1"]);
+    assert_eq!(service.request("GET", "/healthz", None).status, 200);
+    assert!(TcpStream::connect(("::1", service.port)).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unix_socket_serves_without_a_tcp_port() {
+    use std::os::unix::net::UnixStream;
+
+    let socket = std::env::temp_dir().join(format!("lint-{}.sock", std::process::id()));
+    let port = free_port();
+    let mut child = service_command(port)
+        .args(["--unix-socket", socket.to_str().unwrap()])
+        .spawn()
+        .expect("Failed to start linter service");
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut stream = loop {
+        if let Ok(stream) = UnixStream::connect(&socket) {
+            break stream;
+        }
+        assert!(Instant::now() < deadline, "Service did not start listening on {}", socket.display());
+        sleep(Duration::from_millis(50));
+    };
+    let body = json!({"language": "python", "code": "x = 1\n"}).to_string();
+    write!(
+        stream,
+        "POST /v1/lint HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.ends_with("[]"), "{}", response);
+    assert!(TcpStream::connect(("127.0.0.1", port)).is_err());
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(&socket);
+}
+
+#[test]
+fn test_lint_request_selects_rules_and_output_format() {
+    let service = Service::start(&[]);
+    let code = "print(1)\nx = (\n";
+
+    let response = service.post_json("/v1/lint", &json!({"filename": "src/app.py", "code": code}));
+    let rules: Vec<Value> = response.json().as_array().unwrap().iter().map(|e| e["rule"].clone()).collect();
+    assert!(rules.contains(&json!("python/print-statement")) && rules.contains(&json!("python/syntax-error")));
+
+    let strict = json!({"filename": "src/app.py", "code": code, "rules": ["print-statement"]});
+    assert_eq!(service.post_json("/v1/lint", &strict).json()[0]["rule"], "python/print-statement");
+    assert_eq!(service.post_json("/v1/lint", &strict).json().as_array().unwrap().len(), 1);
+
+    let lenient = json!({"language": "python", "code": code, "min_severity": "error", "disable": ["python/missing-token"]});
+    let errors = service.post_json("/v1/lint", &lenient).json();
+    assert!(errors.as_array().unwrap().iter().all(|e| e["severity"] == "error" && e["rule"] == "python/syntax-error"));
+
+    let targeted = json!({"language": "java", "code": "class A { void f() { var x = 1; } }\n", "target_version": "1.8"});
+    assert_eq!(service.post_json("/v1/lint", &targeted).json()[0]["rule"], "java/target-version");
+
+    let unix = service.post_json("/v1/lint", &json!({"filename": "app.py", "code": code, "rules": ["print-statement"], "format": "unix"}));
+    assert!(unix.header("content-type").unwrap().starts_with("text/plain"));
+    assert_eq!(unix.body, "app.py:1:1: warning: Use of print statements is discouraged [python/print-statement]\n");
+    let ndjson = service.post_json("/v1/lint", &json!({"filename": "ok.py", "code": "x = 1\n", "format": "ndjson"}));
+    assert_eq!(ndjson.body, "{\"path\":\"ok.py\",\"errors\":[]}\n");
+
+    let unknown = service.post_json("/v1/lint", &json!({"language": "python", "code": code, "rules": ["arrow-assignment"]}));
+    assert_eq!(unknown.status, 400);
+    assert_eq!(unknown.json()["error"], "Unknown rule 'arrow-assignment' for python");
+    let outdated = service.post_json("/v1/lint", &json!({"language": "java", "code": code, "target_version": "x"}));
+    assert_eq!(outdated.status, 400);
+    let undetectable = service.post_json("/v1/lint", &json!({"filename": "notes.txt", "code": code}));
+    assert_eq!(undetectable.status, 400);
+    assert!(undetectable.json()["error"].as_str().unwrap().starts_with("Cannot detect the language"));
+}
+
+#[test]
+fn test_latency_histograms_and_volume_metrics() {
+    let service = Service::start(&["--parse-timeout-ms", "1"]);
+    let body = json!({"language": "python", "code": "x = 1\ny = 2\n"});
+    service.post_json("/v1/lint", &body);
+    service.post_json("/v1/lint", &body);
+    let slow = "class A { void f() { int x = 1; } }\n".repeat(200_000);
+    assert_eq!(service.post_json("/v1/lint", &json!({"language": "java", "code": slow})).status, 422);
+
+    let metrics = service.request("GET", "/metrics", None).body;
+    for line in [
+        "lint_request_duration_seconds_count{language=\"python\",outcome=\"ok\"} 1",
+        "lint_request_duration_seconds_count{language=\"python\",outcome=\"cached\"} 1",
+        "lint_request_duration_seconds_count{language=\"java\",outcome=\"timeout\"} 1",
+        "lint_parse_duration_seconds_count{language=\"python\"} 1",
+        "lint_check_duration_seconds_count{language=\"python\"} 1",
+        "lint_bytes_total{language=\"python\"} 24",
+        "lint_lines_total{language=\"python\"} 4",
+        "lint_in_flight 0",
+    ] {
+        assert!(metrics.contains(line), "{} not in {}", line, metrics);
+    }
+    assert!(metrics.contains("lint_request_duration_seconds_bucket{language=\"python\",outcome=\"ok\",le=\"0.0005\"}"));
+    assert!(!metrics.contains("lint_duration_seconds"), "{}", metrics);
+}
+
+#[test]
+fn test_metric_labels_are_bounded_to_supported_languages() {
+    let service = Service::start(&[]);
+    for language in ["cobol", "brainfuck-3000"] {
+        let errors = service.post_json("/v1/lint", &json!({"language": language, "code": "x"})).json();
+        assert_eq!(errors[0]["rule"], "linter-error");
+    }
+    service.post_json("/v1/lint", &json!({"language": "r", "code": "x <- 1\ny <- 2\n"}));
+
+    let metrics = service.request("GET", "/metrics", None).body;
+    assert!(!metrics.contains("cobol") && !metrics.contains("brainfuck"), "{}", metrics);
+    assert!(metrics.contains("lint_requests_by_language{language=\"unknown\"} 2"), "{}", metrics);
+    assert!(metrics.contains("lint_requests_by_language{language=\"r\"} 1"), "{}", metrics);
+    assert!(metrics.contains("lint_unsupported_language_total 2"), "{}", metrics);
+    assert!(metrics.contains("lint_diagnostics_by_rule{rule=\"r/arrow-assignment\"} 2"), "{}", metrics);
+}
+
+#[test]
+fn test_request_id_is_echoed_or_generated() {
+    let service = Service::start(&[]);
+    let body = json!({"language": "python", "code": "x = 1\n"}).to_string();
+
+    let response = service.send("POST", "/v1/lint", &[("Content-Type", "application/json"), ("X-Request-Id", "gw-123")], body.as_bytes());
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("x-request-id"), Some("gw-123"));
+
+    let first = service.request("POST", "/v1/lint", Some(&body));
+    let second = service.request("GET", "/healthz", None);
+    let (first, second) = (first.header("x-request-id").unwrap(), second.header("x-request-id").unwrap());
+    assert_eq!(first.len(), 36, "{}", first);
+    assert_ne!(first, second);
+
+    // IDs that could not be logged or sent back unchanged are replaced.
+    let response = service.send("GET", "/healthz", &[("X-Request-Id", &"x".repeat(200))], b"");
+    assert_eq!(response.header("x-request-id").unwrap().len(), 36);
+}
+
+#[test]
+fn test_request_id_appears_in_log_lines() {
+    let log_path = std::env::temp_dir().join(format!("lint-request-id-{}.log", std::process::id()));
+    let log = std::fs::File::create(&log_path).unwrap();
+    let service = Service::start_logging(&[], "lintymclintface=debug", &log);
+    let body = json!([{"path": "a.py", "language": "python", "code": "print 1\n"}]).to_string();
+    let response = service.send("POST", "/v1/lint/batch", &[("Content-Type", "application/json"), ("X-Request-Id", "trace-me-42")], body.as_bytes());
+    assert_eq!(response.status, 200);
+    drop(service);
+
+    let logs = std::fs::read_to_string(&log_path).unwrap();
+    let _ = std::fs::remove_file(&log_path);
+    let lines: Vec<&str> = logs.lines().filter(|line| line.contains("batch lint request")).collect();
+    assert_eq!(lines.len(), 1, "{}", logs);
+    assert!(lines[0].contains("request_id=trace-me-42"), "{}", lines[0]);
+    // Work done on the thread pool is logged under the request too.
+    assert!(logs.lines().any(|line| line.contains("request_id=trace-me-42") && line.contains("rule_pass{pass=\"checks\"}")), "{}", logs);
+}
@@ -0,0 +1,107 @@
+// The mock collector is a tonic server, which only the `grpc` feature brings in.
+#![cfg(all(feature = "otel", feature = "grpc"))]
+
+mod common;
+
+use common::{free_port, service_command, Service};
+use opentelemetry_proto::tonic::collector::trace::v1::trace_service_server::{TraceService, TraceServiceServer};
+use opentelemetry_proto::tonic::collector::trace::v1::{ExportTraceServiceRequest, ExportTraceServiceResponse};
+use opentelemetry_proto::tonic::trace::v1::Span;
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// An OTLP collector that keeps the spans it receives.
+#[derive(Clone, Default)]
+struct Collector {
+    spans: Arc<Mutex<Vec<Span>>>,
+}
+
+#[tonic::async_trait]
+impl TraceService for Collector {
+    async fn export(
+        &self,
+        request: tonic::Request<ExportTraceServiceRequest>,
+    ) -> Result<tonic::Response<ExportTraceServiceResponse>, tonic::Status> {
+        let spans = request
+            .into_inner()
+            .resource_spans
+            .into_iter()
+            .flat_map(|r| r.scope_spans)
+            .flat_map(|s| s.spans);
+        self.spans.lock().unwrap().extend(spans);
+        Ok(tonic::Response::new(ExportTraceServiceResponse { partial_success: None }))
+    }
+}
+
+/// Starts `lintymclintface --service` exporting its spans to `otlp_endpoint`.
+fn start(otlp_endpoint: &str) -> Service {
+    let port = free_port();
+    let mut command = service_command(port);
+    command
+        .args(["--otlp-endpoint", otlp_endpoint])
+        // Export every 100 ms rather than every 5 s.
+        .env("OTEL_BSP_SCHEDULE_DELAY", "100");
+    Service::launch(&mut command, port, port)
+}
+
+#[test]
+fn test_spans_are_exported_to_the_collector() {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap();
+    let collector = Collector::default();
+    let listener = runtime.block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    runtime.spawn(
+        tonic::transport::Server::builder()
+            .add_service(TraceServiceServer::new(collector.clone()))
+            .serve_with_incoming(tokio_stream(listener)),
+    );
+
+    let service = start(&endpoint);
+    let trace_id = "4bf92f3577b34da6a3ce929d0e0e4736";
+    let response = service.send(
+        "POST",
+        "/v1/lint",
+        &[
+            ("Content-Type", "application/json"),
+            ("traceparent", &format!("00-{}-00f067aa0ba902b7-01", trace_id)),
+        ],
+        br#"{"language": "python", "code": "print 1\n", "target_version": "3.8"}"#,
+    );
+    assert_eq!(response.status, 200, "{}", response.body);
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let names = loop {
+        let spans = collector.spans.lock().unwrap().clone();
+        if spans.iter().any(|s| s.name == "http_request") || Instant::now() > deadline {
+            break spans;
+        }
+        drop(spans);
+        sleep(Duration::from_millis(100));
+    };
+    let names: Vec<&str> = names.iter().map(|s| s.name.as_str()).collect();
+    for expected in ["http_request", "lint", "parse", "rule_pass"] {
+        assert!(names.contains(&expected), "no {} span in {:?}", expected, names);
+    }
+    assert_eq!(names.iter().filter(|name| **name == "rule_pass").count(), 2, "{:?}", names);
+
+    // The spans continue the trace the caller started.
+    let spans = collector.spans.lock().unwrap();
+    let request = spans.iter().find(|s| s.name == "http_request").unwrap();
+    assert_eq!(hex(&request.trace_id), trace_id);
+    assert_eq!(hex(&request.parent_span_id), "00f067aa0ba902b7");
+    assert!(spans.iter().filter(|s| s.name != "http_request").all(|s| s.trace_id == request.trace_id));
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn tokio_stream(
+    listener: tokio::net::TcpListener,
+) -> impl futures_util::Stream<Item = std::io::Result<tokio::net::TcpStream>> {
+    futures_util::stream::unfold(listener, |listener| async move {
+        let accepted = listener.accept().await.map(|(stream, _)| stream);
+        Some((accepted, listener))
+    })
+}
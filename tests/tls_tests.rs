@@ -0,0 +1,129 @@
+#![cfg(feature = "tls")]
+
+mod common;
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use common::{free_port, service_command, Service};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, WebPkiSupportedAlgorithms};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, ClientConnection, DigitallySignedStruct, SignatureScheme, StreamOwned};
+
+/// Accepts any certificate, so that tests can check which one the service presented.
+#[derive(Debug)]
+struct AcceptAnyCertificate(WebPkiSupportedAlgorithms);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.0)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.0)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.supported_schemes()
+    }
+}
+
+/// Writes a new self-signed certificate for `localhost` and its key, returning the certificate.
+fn write_certificate(cert_path: &Path, key_path: &Path) -> Vec<u8> {
+    let generated = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    std::fs::write(key_path, generated.key_pair.serialize_pem()).unwrap();
+    std::fs::write(cert_path, generated.cert.pem()).unwrap();
+    generated.cert.der().to_vec()
+}
+
+/// Starts `lintymclintface --service` serving TLS with the given certificate and key.
+fn start(cert_path: &Path, key_path: &Path) -> Service {
+    let port = free_port();
+    let mut command = service_command(port);
+    command
+        .args(["--bind", "127.0.0.1", "--tls-cert"])
+        .arg(cert_path)
+        .arg("--tls-key")
+        .arg(key_path);
+    Service::launch(&mut command, port, port)
+}
+
+/// Sends `GET path` over TLS, returning the response and the certificate presented.
+fn get(service: &Service, path: &str) -> (String, Vec<u8>) {
+    let provider = Arc::new(ring::default_provider());
+    let verifier = AcceptAnyCertificate(provider.signature_verification_algorithms);
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    let connection = ClientConnection::new(Arc::new(config), "localhost".try_into().unwrap()).unwrap();
+    let socket = TcpStream::connect(("127.0.0.1", service.port)).unwrap();
+    let mut stream = StreamOwned::new(connection, socket);
+    write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path).unwrap();
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response);
+    let certificate = stream.conn.peer_certificates().unwrap()[0].to_vec();
+    (response, certificate)
+}
+
+#[test]
+fn test_tls_certificate_is_served_and_reloaded() {
+    let dir = std::env::temp_dir().join(format!("lint-tls-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (cert_path, key_path) = (dir.join("cert.pem"), dir.join("key.pem"));
+    let first = write_certificate(&cert_path, &key_path);
+    let service = start(&cert_path, &key_path);
+
+    let (response, certificate) = get(&service, "/healthz");
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert_eq!(certificate, first);
+
+    // Plain HTTP is not served on the TLS port.
+    let mut plain = TcpStream::connect(("127.0.0.1", service.port)).unwrap();
+    plain.write_all(b"GET /healthz HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+    let mut response = Vec::new();
+    let _ = plain.read_to_end(&mut response);
+    assert!(!response.starts_with(b"HTTP/1.1 200"));
+
+    sleep(Duration::from_millis(1100));
+    let second = write_certificate(&cert_path, &key_path);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let (response, certificate) = get(&service, "/healthz");
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        if certificate == second {
+            break;
+        }
+        assert!(Instant::now() < deadline, "The renewed certificate was not picked up");
+        sleep(Duration::from_millis(200));
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}